@@ -4,9 +4,25 @@ use clap_complete::{
     Shell::{Bash, Elvish, Fish, PowerShell, Zsh},
 };
 
+use std::path::{Path, PathBuf};
+
 // Include the Cli struct.
 include!("src/cli.rs");
 
+// Include the output formats of `dym check`, which the Cli struct names by their library path.
+#[allow(dead_code)]
+mod didyoumean {
+    pub mod report {
+        include!("src/report/format.rs");
+    }
+}
+
+// Include the supported languages.
+#[allow(dead_code)]
+mod langs {
+    include!("src/langs.rs");
+}
+
 fn main() {
     // Get directories.
     let root_dir = std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"));
@@ -21,14 +37,84 @@ fn main() {
     let mut cmd = Cli::command();
     cmd.set_bin_name("dym");
 
-    // Generate man page.
-    let man = clap_mangen::Man::new(cmd.to_owned());
-    let mut buffer: Vec<u8> = Default::default();
-    man.render(&mut buffer).expect("Man page generation failed");
-    std::fs::write(man_dir.join("dym.1"), buffer).expect("Failed to write man page");
+    // Generate man pages for dym and each of its subcommands (dym.1, dym-lang-list.1, etc.).
+    clap_mangen::generate_to(cmd.to_owned(), &man_dir).expect("Man page generation failed");
 
     // Generate shell completions.
     for shell in [Bash, Elvish, Fish, PowerShell, Zsh] {
         generate_to(shell, &mut cmd, "dym", &comp_dir).unwrap();
     }
+
+    embed_word_lists(&root_dir);
+}
+
+/// Compress the word lists selected with the `embed-*` features into `OUT_DIR` and generate the
+/// `EMBEDDED` table included by `src/embedded.rs`. The table is empty if no feature is enabled.
+///
+/// # Arguments
+///
+/// * `root_dir` - The directory holding `Cargo.toml`.
+fn embed_word_lists(root_dir: &Path) {
+    println!("cargo:rerun-if-env-changed=DYM_EMBED_DIR");
+    let embed_dir = std::env::var_os("DYM_EMBED_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| root_dir.join("wordlists"));
+    let out_dir = PathBuf::from(std::env::var_os("OUT_DIR").unwrap());
+    let all = std::env::var_os("CARGO_FEATURE_EMBED_ALL").is_some();
+
+    let mut langs: Vec<&str> = langs::SUPPORTED_LANGS.keys().cloned().collect();
+    langs.sort();
+
+    let mut entries = String::new();
+    for lang in langs {
+        // `embed-all` only embeds the word lists that are present.
+        let path = embed_dir.join(lang);
+        let feature = format!("CARGO_FEATURE_EMBED_{}", lang.to_uppercase());
+        if std::env::var_os(feature).is_none() && !(all && path.is_file()) {
+            continue;
+        }
+
+        println!("cargo:rerun-if-changed={}", path.display());
+        let word_list = std::fs::read(&path).unwrap_or_else(|error| {
+            panic!(
+                "The embed-{} feature needs the word list {}: {}. Set DYM_EMBED_DIR to the directory holding the word lists.",
+                lang,
+                path.display(),
+                error
+            )
+        });
+        if std::str::from_utf8(&word_list).is_err() {
+            panic!("The word list {} is not valid UTF-8", path.display());
+        }
+
+        let compressed_path = out_dir.join(format!("{}.zst", lang));
+        std::fs::write(
+            &compressed_path,
+            zstd::encode_all(&word_list[..], 19).unwrap(),
+        )
+        .unwrap();
+        entries.push_str(&format!(
+            "    ({:?}, include_bytes!({:?})),\n",
+            lang, compressed_path
+        ));
+    }
+
+    if all {
+        println!("cargo:rerun-if-changed={}", embed_dir.display());
+        if entries.is_empty() {
+            println!(
+                "cargo:warning=embed-all is enabled but no word lists were found in {}",
+                embed_dir.display()
+            );
+        }
+    }
+
+    std::fs::write(
+        out_dir.join("embedded.rs"),
+        format!(
+            "pub static EMBEDDED: &[(&str, &[u8])] = &[\n{}];\n",
+            entries
+        ),
+    )
+    .unwrap();
 }
@@ -0,0 +1,259 @@
+use flate2::{read::GzDecoder, write::GzEncoder};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::BTreeMap,
+    fs::{read, remove_file, rename, write, File},
+    io::{Error, ErrorKind, Read},
+    path::Path,
+    time::SystemTime,
+};
+use tar::{Archive, Builder, Header};
+
+use crate::store::{self, Metadata};
+use didyoumean::{compression::decompress, data::DataDirs, langs::SUPPORTED_LANGS};
+
+/// Name of the file listing the word lists in a bundle.
+const MANIFEST: &str = "bundle.toml";
+
+/// Version of the bundle format written by `export`.
+const VERSION: u32 = 1;
+
+/// The list of word lists in a bundle, stored in `bundle.toml` at the start of the archive.
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct Manifest {
+    /// Version of the bundle format.
+    version: u32,
+    /// Metadata of each word list, by locale code.
+    word_lists: BTreeMap<String, Metadata>,
+}
+
+/// Pack the installed word lists for `langs` into a gzip compressed tar archive at `path`, along
+/// with their metadata. Return the locale codes of the exported word lists.
+///
+/// # Arguments
+///
+/// * `path` - The path to write the bundle to.
+/// * `langs` - The locale codes of the word lists to export. All installed word lists are exported
+///   if empty.
+/// * `data_dirs` - The directories holding the word lists.
+pub fn export(path: &Path, langs: &[String], data_dirs: &DataDirs) -> Result<Vec<String>, Error> {
+    let mut langs: Vec<String> = if langs.is_empty() {
+        SUPPORTED_LANGS
+            .keys()
+            .filter(|lang| data_dirs.find(lang).is_some())
+            .map(|lang| lang.to_string())
+            .collect()
+    } else {
+        langs.to_vec()
+    };
+    langs.sort();
+    langs.dedup();
+
+    if langs.is_empty() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            "No word lists are installed",
+        ));
+    }
+
+    // Read and check every word list before writing anything.
+    let mut manifest = Manifest {
+        version: VERSION,
+        word_lists: BTreeMap::new(),
+    };
+    let mut files = vec![];
+    for lang in &langs {
+        let word_list = data_dirs.find(lang).ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!("{} word list is not installed", lang),
+            )
+        })?;
+        store::verify(&word_list).map_err(|error| {
+            Error::new(
+                error.kind(),
+                format!("{} word list is corrupt: {}", lang, error),
+            )
+        })?;
+
+        let contents = read(&word_list)?;
+        let sha256 = checksum(&contents).map_err(|error| {
+            Error::new(
+                error.kind(),
+                format!("{} word list is corrupt: {}", lang, error),
+            )
+        })?;
+
+        // Keep the validators of downloaded word lists so updates stay conditional.
+        let metadata = Metadata {
+            size: contents.len() as u64,
+            sha256,
+            ..store::read_metadata(&word_list).unwrap_or_default()
+        };
+        manifest.word_lists.insert(lang.to_owned(), metadata);
+        files.push((lang.to_owned(), contents));
+    }
+
+    // Write the archive to a temporary file and move it into place once it is complete.
+    let partial = store::partial_path(path);
+    let result = write_archive(&partial, &manifest, &files).and_then(|_| rename(&partial, path));
+    if result.is_err() {
+        remove_file(&partial).ok();
+    }
+    result?;
+
+    Ok(langs)
+}
+
+/// Validate the word lists in the bundle at `path` and install them into the user data directory,
+/// replacing installed copies. Nothing is installed if any word list in the bundle is invalid.
+/// Return the locale codes of the imported word lists.
+///
+/// # Arguments
+///
+/// * `path` - The path of a bundle written by `export`.
+/// * `data_dirs` - The directories holding the word lists.
+pub fn import(path: &Path, data_dirs: &DataDirs) -> Result<Vec<String>, Error> {
+    let invalid = |message: String| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Invalid bundle {}: {}", path.display(), message),
+        )
+    };
+
+    // Read every file in the archive. Word lists are small enough to keep in memory.
+    let mut archive = Archive::new(GzDecoder::new(File::open(path)?));
+    let mut files = BTreeMap::new();
+    for entry in archive
+        .entries()
+        .map_err(|error| invalid(error.to_string()))?
+    {
+        let mut entry = entry.map_err(|error| invalid(error.to_string()))?;
+        let name = entry
+            .path()
+            .map_err(|error| invalid(error.to_string()))?
+            .to_string_lossy()
+            .into_owned();
+        let mut contents = vec![];
+        entry
+            .read_to_end(&mut contents)
+            .map_err(|error| invalid(error.to_string()))?;
+        files.insert(name, contents);
+    }
+
+    let manifest = files
+        .remove(MANIFEST)
+        .ok_or_else(|| invalid(format!("{} is missing", MANIFEST)))?;
+    let manifest: Manifest = std::str::from_utf8(&manifest)
+        .ok()
+        .and_then(|manifest| toml::from_str(manifest).ok())
+        .ok_or_else(|| invalid(format!("{} can't be read", MANIFEST)))?;
+    if manifest.version > VERSION {
+        return Err(invalid(format!(
+            "version {} is not supported, update dym to import it",
+            manifest.version
+        )));
+    }
+
+    // Check every word list before installing anything.
+    if let Some(name) = files
+        .keys()
+        .find(|name| !manifest.word_lists.contains_key(*name))
+    {
+        return Err(invalid(format!("{} is not listed in {}", name, MANIFEST)));
+    }
+    for (lang, metadata) in &manifest.word_lists {
+        if !SUPPORTED_LANGS.contains_key(lang) {
+            return Err(invalid(format!("{} is not a supported language", lang)));
+        }
+
+        let contents = files
+            .get(lang)
+            .ok_or_else(|| invalid(format!("{} word list is missing", lang)))?;
+        if contents.len() as u64 != metadata.size {
+            return Err(invalid(format!(
+                "{} word list has {} bytes instead of {}",
+                lang,
+                contents.len(),
+                metadata.size
+            )));
+        }
+
+        let sha256 = checksum(contents)
+            .map_err(|error| invalid(format!("{} word list is corrupt: {}", lang, error)))?;
+        if sha256 != metadata.sha256 {
+            return Err(invalid(format!(
+                "{} word list has checksum {} instead of {}",
+                lang, sha256, metadata.sha256
+            )));
+        }
+    }
+
+    // Install each word list atomically, in the same way downloads are.
+    let data = data_dirs.ensure_user()?;
+    for (lang, metadata) in &manifest.word_lists {
+        let word_list = data.join(lang);
+        let partial = store::partial_path(&word_list);
+        write(&partial, &files[lang])?;
+        rename(&partial, &word_list)?;
+        store::write_metadata(&word_list, metadata)?;
+    }
+
+    Ok(manifest.word_lists.into_keys().collect())
+}
+
+/// Write a bundle holding `manifest` and `files` to `path`.
+///
+/// # Arguments
+///
+/// * `path` - The path to write the bundle to.
+/// * `manifest` - The list of word lists in the bundle.
+/// * `files` - The locale code and contents of each word list.
+fn write_archive(
+    path: &Path,
+    manifest: &Manifest,
+    files: &[(String, Vec<u8>)],
+) -> Result<(), Error> {
+    let mtime = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let manifest = toml::to_string(manifest).map_err(Error::other)?;
+
+    let mut builder = Builder::new(GzEncoder::new(
+        File::create(path)?,
+        flate2::Compression::default(),
+    ));
+    let entries = std::iter::once((MANIFEST, manifest.as_bytes())).chain(
+        files
+            .iter()
+            .map(|(lang, contents)| (lang.as_str(), &contents[..])),
+    );
+    for (name, contents) in entries {
+        let mut header = Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        builder.append_data(&mut header, name, contents)?;
+    }
+
+    builder.into_inner()?.finish()?.sync_all()
+}
+
+/// Return the hex encoded SHA-256 checksum of a word list, decompressing it first if it is stored
+/// compressed. Return an `InvalidData` error if it isn't valid UTF-8.
+///
+/// # Arguments
+///
+/// * `contents` - The contents of a word list file.
+fn checksum(contents: &[u8]) -> Result<String, Error> {
+    let contents = decompress(contents.to_vec())?;
+    if std::str::from_utf8(&contents).is_err() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "the word list is not valid UTF-8",
+        ));
+    }
+
+    Ok(format!("{:x}", Sha256::digest(&contents)))
+}
@@ -0,0 +1,148 @@
+use std::{
+    collections::HashMap,
+    fs::metadata,
+    io::{Error, ErrorKind},
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::SystemTime,
+};
+
+use crate::{config::Config, embedded, hunspell_dictionary, load_offline};
+use didyoumean::{data::DataDirs, dictionary::Dictionary, langs::SUPPORTED_LANGS};
+
+/// A loaded dictionary, along with the modification times of the files it was loaded from so it
+/// is reloaded when they change.
+struct Entry {
+    dictionary: Arc<Dictionary>,
+    stamp: Vec<Option<SystemTime>>,
+}
+
+/// Dictionaries kept in memory by long-running commands, shared between threads.
+pub struct Dictionaries {
+    config: Config,
+    data_dirs: DataDirs,
+    entries: Mutex<HashMap<String, Entry>>,
+    /// The Hunspell dictionary of each language looked up so far, so the directories aren't
+    /// searched on every request.
+    hunspell: Mutex<HashMap<String, Option<PathBuf>>>,
+}
+
+impl Dictionaries {
+    /// Create an empty cache of the word lists in `data_dirs`.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration file.
+    /// * `data_dirs` - The directories holding the word lists.
+    pub fn new(config: Config, data_dirs: DataDirs) -> Dictionaries {
+        Dictionaries {
+            config,
+            data_dirs,
+            entries: Mutex::new(HashMap::new()),
+            hunspell: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Return the configuration the dictionaries are loaded with.
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// Return the directories the word lists are loaded from.
+    pub fn data_dirs(&self) -> &DataDirs {
+        &self.data_dirs
+    }
+
+    /// Return an `InvalidInput` error if there is neither a word list nor a Hunspell dictionary
+    /// for `lang`.
+    ///
+    /// # Arguments
+    ///
+    /// * `lang` - The locale code of the word list.
+    pub fn validate(&self, lang: &str) -> Result<(), Error> {
+        if !SUPPORTED_LANGS.contains_key(lang) && self.hunspell(lang).is_none() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("There is no word list for {}", lang),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Return the dictionary of `lang`, loading it if it wasn't loaded yet or if its word list
+    /// or personal word list changed since. Nothing is ever downloaded or printed, so a request
+    /// can't stall on a download and the language server's output stays intact. Return an
+    /// `InvalidInput` error for unsupported languages, a `NotFound` error for languages that
+    /// aren't installed and an `InvalidData` error for corrupt word lists.
+    ///
+    /// # Arguments
+    ///
+    /// * `lang` - The locale code of the word list.
+    pub fn get(&self, lang: &str) -> Result<Arc<Dictionary>, Error> {
+        self.validate(lang)?;
+        let hunspell = self.hunspell(lang);
+
+        let stamp = self.stamp(lang, hunspell.as_ref());
+        if let Some(entry) = self.entries.lock().unwrap().get(lang) {
+            if entry.stamp == stamp {
+                return Ok(Arc::clone(&entry.dictionary));
+            }
+        }
+
+        if self.data_dirs.find(lang).is_none()
+            && embedded::size(lang).is_none()
+            && hunspell.is_none()
+        {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("{} word list is not installed", lang),
+            ));
+        }
+        let dictionary = Arc::new(load_offline(lang, &self.data_dirs)?);
+        self.entries.lock().unwrap().insert(
+            lang.to_owned(),
+            Entry {
+                dictionary: Arc::clone(&dictionary),
+                stamp,
+            },
+        );
+        Ok(dictionary)
+    }
+
+    /// Return the path of the Hunspell dictionary of `lang`, looking for it the first time only.
+    ///
+    /// # Arguments
+    ///
+    /// * `lang` - The locale code of the language.
+    fn hunspell(&self, lang: &str) -> Option<PathBuf> {
+        self.hunspell
+            .lock()
+            .unwrap()
+            .entry(lang.to_owned())
+            .or_insert_with(|| hunspell_dictionary(lang, &self.data_dirs))
+            .clone()
+    }
+
+    /// Return the modification times of the word list, Hunspell dictionary and affix files, and
+    /// personal word list of `lang`.
+    ///
+    /// # Arguments
+    ///
+    /// * `lang` - The locale code of the word list.
+    /// * `hunspell` - The Hunspell dictionary of `lang`, if it has one.
+    fn stamp(&self, lang: &str, hunspell: Option<&PathBuf>) -> Vec<Option<SystemTime>> {
+        [
+            self.data_dirs.find(lang),
+            hunspell.cloned(),
+            hunspell.map(|dic| dic.with_extension("aff")),
+            self.data_dirs.personal(lang),
+        ]
+        .iter()
+        .map(|path| {
+            path.as_ref()
+                .and_then(|path| metadata(path).ok())
+                .and_then(|metadata| metadata.modified().ok())
+        })
+        .collect()
+    }
+}
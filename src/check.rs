@@ -0,0 +1,131 @@
+use std::collections::HashMap;
+
+use crate::dictionary::Dictionary;
+
+/// A word found in a piece of text, with its 1-based line and column (counted in characters).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Word<'a> {
+    pub text: &'a str,
+    pub line: usize,
+    pub column: usize,
+}
+
+/// A word that is not in the dictionary, along with the closest known words.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Misspelling {
+    pub word: String,
+    pub line: usize,
+    pub column: usize,
+    pub suggestions: Vec<String>,
+}
+
+/// Return `true` if `c` can appear inside a word, but not at its start or end.
+fn is_joiner(c: char) -> bool {
+    c == '\'' || c == '’'
+}
+
+/// Split `text` into words. A word is a run of alphabetic characters, optionally joined by
+/// apostrophes. Runs that touch digits or underscores (identifiers, hashes, etc.) are skipped.
+///
+/// # Arguments
+///
+/// * `text` - The text to split.
+///
+/// # Examples
+///
+/// ```
+/// # use didyoumean::check::words;
+/// let found: Vec<_> = words("Don't panic,\nsha1 ok").map(|w| (w.text, w.line, w.column)).collect();
+/// assert_eq!(found, vec![("Don't", 1, 1), ("panic", 1, 7), ("ok", 2, 6)]);
+/// ```
+pub fn words(text: &str) -> impl Iterator<Item = Word<'_>> {
+    text.lines().enumerate().flat_map(|(line_index, line)| {
+        let chars: Vec<(usize, char)> = line.char_indices().collect();
+        let mut found = vec![];
+        let mut i = 0;
+
+        while i < chars.len() {
+            if !chars[i].1.is_alphabetic() {
+                i += 1;
+                continue;
+            }
+
+            // Extend the word over letters and apostrophes followed by a letter.
+            let start = i;
+            while i < chars.len()
+                && (chars[i].1.is_alphabetic()
+                    || (is_joiner(chars[i].1)
+                        && i + 1 < chars.len()
+                        && chars[i + 1].1.is_alphabetic()))
+            {
+                i += 1;
+            }
+
+            // Skip words that are part of a larger token such as `sha1` or `snake_case`.
+            let touches_token = |c: char| c.is_alphanumeric() || c == '_';
+            let before = start.checked_sub(1).map(|j| chars[j].1);
+            let after = chars.get(i).map(|&(_, c)| c);
+            if before.is_some_and(touches_token) || after.is_some_and(touches_token) {
+                continue;
+            }
+
+            let end = chars.get(i).map_or(line.len(), |&(byte, _)| byte);
+            found.push(Word {
+                text: &line[chars[start].0..end],
+                line: line_index + 1,
+                column: start + 1,
+            });
+        }
+
+        found
+    })
+}
+
+/// Return the words in `text` that are not in `dictionary`, in the order they appear, each with
+/// up to `n` suggestions.
+///
+/// # Arguments
+///
+/// * `text` - The text to check.
+/// * `dictionary` - The known words.
+/// * `n` - The maximum number of suggestions for each misspelled word.
+///
+/// # Examples
+///
+/// ```
+/// # use didyoumean::{check::check, dictionary::Dictionary};
+/// let dictionary = Dictionary::parse("the\ncat\nsat");
+/// let misspellings = check("The cat sta", &dictionary, 1);
+///
+/// assert_eq!(misspellings.len(), 1);
+/// assert_eq!(misspellings[0].word, "sta");
+/// assert_eq!(misspellings[0].column, 9);
+/// assert_eq!(misspellings[0].suggestions, vec!["sat"]);
+/// ```
+pub fn check(text: &str, dictionary: &Dictionary, n: usize) -> Vec<Misspelling> {
+    // Suggestions are expensive, so only compute them once for each misspelled word.
+    let mut cache: HashMap<&str, Vec<String>> = HashMap::new();
+
+    words(text)
+        .filter(|word| !dictionary.contains(word.text))
+        .map(|word| {
+            let suggestions = cache
+                .entry(word.text)
+                .or_insert_with(|| {
+                    dictionary
+                        .suggest(word.text, n)
+                        .into_iter()
+                        .map(|suggestion| suggestion.word.to_owned())
+                        .collect()
+                })
+                .clone();
+
+            Misspelling {
+                word: word.text.to_owned(),
+                line: word.line,
+                column: word.column,
+                suggestions,
+            }
+        })
+        .collect()
+}
@@ -1,18 +1,211 @@
-use clap::Parser;
+use clap::{Args, Parser, Subcommand};
+use didyoumean::report::Format;
 
-// Parse command line arguments to get the search term.
+// Parse command line arguments. Running `dym` without a subcommand is a shortcut for `dym suggest`.
 #[derive(Parser)]
-#[clap(author = "Hisbaan Noorani", version = "1.1.4", about = "Did You Mean: A cli spelling corrector", long_about = None)]
+#[clap(
+    name = "dym",
+    author = "Hisbaan Noorani",
+    version = "1.1.4",
+    about = "Did You Mean: A cli spelling corrector",
+    long_about = None
+)]
 pub struct Cli {
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+    #[clap(flatten)]
+    pub suggest: SuggestArgs,
+    #[clap(
+        long = "data-dir",
+        value_name = "DIR",
+        global = true,
+        help = "Use DIR to store downloaded word lists",
+        long_help = "Use DIR to store downloaded word lists instead of the platform data directory. This can also be set with the DYM_DATA_DIR environment variable or the data_dir configuration key. System-wide word lists in /usr/share/didyoumean are still searched first."
+    )]
+    pub data_dir: Option<std::path::PathBuf>,
+    #[clap(
+        long = "source-url",
+        value_name = "URL",
+        global = true,
+        help = "Download word lists from URL",
+        long_help = "Download word lists from URL instead of https://github.com/hisbaan/wordlists. The word list for a language is expected at URL/<LANG>, with optional compressed copies at URL/<LANG>.zst and URL/<LANG>.gz that are preferred if present. An optional SHA256SUMS manifest at URL/SHA256SUMS listing the uncompressed word lists is used to verify downloads. file:// URLs are read from the file system. This can also be set with the DYM_SOURCE_URL environment variable or the source_url configuration key."
+    )]
+    pub source_url: Option<String>,
+    #[clap(
+        long = "mirror",
+        value_name = "URL",
+        global = true,
+        help = "Try downloading from URL if the source fails (can be repeated)",
+        long_help = "Try downloading word lists from URL if downloading from the source fails. Mirrors are tried in the order they are given. This can also be set with the DYM_MIRRORS environment variable as a whitespace separated list, or the mirrors configuration key."
+    )]
+    pub mirrors: Vec<String>,
+    #[clap(
+        long = "timeout",
+        value_name = "SECS",
+        global = true,
+        value_parser = clap::value_parser!(u64).range(1..),
+        help = "Give up on a download after SECS seconds without progress",
+        long_help = "Give up on a download when connecting or receiving data takes longer than SECS seconds. Defaults to 30. This can also be set with the timeout configuration key."
+    )]
+    pub timeout: Option<u64>,
+    #[clap(
+        long = "retries",
+        value_name = "N",
+        global = true,
+        help = "Retry failed downloads N times",
+        long_help = "Retry a download up to N times when it fails because of a network error or a server error, waiting twice as long before each retry. Interrupted downloads are resumed where they stopped if the server supports it. Defaults to 3. This can also be set with the retries configuration key."
+    )]
+    pub retries: Option<u32>,
+    // Deprecated spellings of `dym lang list` and `dym lang update`.
+    #[clap(long = "print-langs", hide = true)]
+    pub print_langs: bool,
+    #[clap(long = "update-langs", hide = true)]
+    pub update_langs: bool,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    #[clap(
+        about = "Suggest corrections for a word (default)",
+        long_about = "Suggest corrections for a word. This is what runs when no subcommand is given, so `dym WORD` is equivalent to `dym suggest WORD`. Use `dym suggest` to look up words that are also subcommand names."
+    )]
+    Suggest(SuggestArgs),
+    #[clap(
+        about = "Check text for misspelled words",
+        long_about = "Check the given files, or standard input if none are given, for words that are not in the word list and print suggestions for each of them. Exits with status 1 if any misspelled words are found."
+    )]
+    Check(CheckArgs),
+    #[clap(
+        about = "Find words interactively",
+        long_about = "Open a finder that updates the suggestions on every keystroke. Use the arrow keys to select a suggestion, enter to print it to standard output, ctrl-y to copy it to the clipboard, and tab or shift-tab to switch between the installed languages. The finder is drawn on standard error, so the chosen word can be captured. Exits with status 1 if no word is chosen."
+    )]
+    Interactive(InteractiveArgs),
+    #[clap(
+        about = "Look up words one after another",
+        long_about = "Start a session reading words line by line and printing suggestions for each of them, loading word lists only once. Lines starting with a colon are commands: `:lang LANG` switches the language, `:n N` changes the number of suggestions, `:add WORD...` adds words to your personal word list, and `:help` lists every command. On a terminal, previous lines can be recalled with the arrow keys."
+    )]
+    Repl(ReplArgs),
+    #[clap(
+        about = "Serve suggestions from memory over a Unix domain socket",
+        long_about = "Keep word lists loaded in memory and answer queries over a Unix domain socket, so editors and scripts running dym for every word don't pay for loading the word list each time. `dym suggest` uses a running daemon automatically and searches on its own otherwise. The socket is daemon.sock in the data directory unless the socket configuration key is set. Requests and responses are single lines of JSON, for example {\"command\": \"suggest\", \"word\": \"helo\", \"lang\": \"en\", \"number\": 5} is answered with {\"suggestions\": [{\"word\": \"hello\", \"distance\": 1}, ...]}, and {\"command\": \"ping\"} with {\"version\": \"...\"}. Failed requests are answered with {\"error\": \"...\"}. Word lists are reloaded when they or the personal word list change."
+    )]
+    Daemon {
+        #[clap(
+            value_name = "LANG",
+            help = "Load the word lists of LANG right away [default: the lang configuration key]"
+        )]
+        langs: Vec<String>,
+        #[clap(
+            long = "socket",
+            value_name = "PATH",
+            help = "Listen on PATH instead of the socket configuration key"
+        )]
+        socket: Option<std::path::PathBuf>,
+    },
+    #[clap(
+        about = "Serve suggestions over HTTP",
+        long_about = "Keep word lists loaded in memory and answer HTTP requests with JSON. GET /suggest?q=WORD&lang=LANG&n=N returns the closest words to WORD, as {\"lang\": \"en\", \"suggestions\": [{\"word\": \"hello\", \"distance\": 1}, ...]}. POST /check?lang=LANG&n=N returns the misspelled words in the body, as {\"lang\": \"en\", \"misspellings\": [{\"word\": \"wrld\", \"line\": 1, \"column\": 5, \"suggestions\": [\"world\"]}, ...]}. The body is plain text, or a JSON object with a text field and optional lang and n fields when sent as application/json. GET /health returns {\"status\": \"ok\", \"version\": \"...\"}. Web pages may call the server from the origins listed in the cors_origins configuration key, or from any origin if it contains \"*\". lang defaults to the lang configuration key and only installed word lists are used. Failed requests are answered with {\"error\": \"...\"}."
+    )]
+    Serve {
+        #[clap(
+            value_name = "LANG",
+            help = "Load the word lists of LANG right away [default: the lang configuration key]"
+        )]
+        langs: Vec<String>,
+        #[clap(
+            long = "bind",
+            value_name = "ADDR:PORT",
+            default_value = "127.0.0.1:8080",
+            help = "Listen on ADDR:PORT"
+        )]
+        bind: String,
+    },
+    #[clap(
+        about = "Run a language server for spell checking",
+        long_about = "Run a Language Server Protocol server on standard input and output, for editors to show misspelled words as diagnostics. Code actions replace a misspelled word with one of its suggestions or add it to your personal word list. The language can also be set with the lang initialization option and the number of suggestions with the number initialization option. Only installed word lists are used."
+    )]
+    Lsp {
+        #[clap(
+            short = 'n',
+            long = "number",
+            default_value_t = 3,
+            help = "Change the number of suggestions offered for each misspelled word"
+        )]
+        number: usize,
+        #[clap(flatten)]
+        lang: LangArgs,
+    },
+    #[clap(
+        about = "Suggest commands for a command that wasn't found",
+        long_about = "Suggest executables on $PATH, and aliases given with --alias, that are close to NAME. This is meant to be run by the command not found handler of a shell, which `dym cmd --hook SHELL` prints for bash, zsh and fish, for example with `eval \"$(dym cmd --hook bash)\"` in ~/.bashrc or `dym cmd --hook fish | source` in the fish configuration. Suggestions are printed to standard error. With --prompt, a suggestion can be chosen and is printed to standard output, and the handler runs it with the original arguments. Exits with status 127 unless a command is chosen."
+    )]
+    Cmd {
+        #[clap(
+            value_name = "NAME",
+            required_unless_present = "hook",
+            help = "The command that wasn't found"
+        )]
+        name: Option<String>,
+        #[clap(
+            long = "alias",
+            value_name = "NAMES",
+            help = "Also suggest the aliases or functions in NAMES, separated by whitespace (can be repeated)"
+        )]
+        aliases: Vec<String>,
+        #[clap(
+            short = 'n',
+            long = "number",
+            help = "Change the number of commands suggested",
+            long_help = "Change the number of commands suggested. The default value is five, or the number configuration key if it is set."
+        )]
+        number: Option<usize>,
+        #[clap(
+            long = "prompt",
+            help = "Choose a command to run instead",
+            long_help = "Choose one of the suggestions and print it to standard output, so the command not found handler can run it. Only prompts when standard error is a terminal."
+        )]
+        prompt: bool,
+        #[clap(
+            long = "hook",
+            value_name = "SHELL",
+            value_parser = ["bash", "zsh", "fish"],
+            conflicts_with = "name",
+            help = "Print the command not found handler of SHELL, prompting to run a suggestion with --prompt",
+            long_help = "Print the command not found handler of SHELL. With --prompt, the handler lets you run a suggestion with the original arguments, and only suggests functions besides executables since aliases can't be run from the handler."
+        )]
+        hook: Option<String>,
+    },
+    #[clap(subcommand, about = "Check the spelling of commit messages")]
+    GitHook(GitHookCommand),
+    #[clap(subcommand, about = "Manage word lists")]
+    Lang(LangCommand),
+    #[clap(subcommand, about = "Manage your personal word list")]
+    Dict(DictCommand),
+    #[clap(subcommand, about = "Show or change the configuration file")]
+    Config(ConfigCommand),
+}
+
+#[derive(Args)]
+pub struct LangArgs {
+    #[clap(
+        short = 'l',
+        long = "lang",
+        help = "Select the desired language using the locale code (en, fr, sp, etc.)",
+        long_help = "Select the desired language using its locale code. For example, English would have the locale code en and French would have the locale code fr. See `dym lang list` for a list of locale codes and the corresponding languages. Defaults to the lang configuration key, or en if it is not set."
+    )]
+    pub lang: Option<String>,
+}
+
+#[derive(Args)]
+pub struct SuggestArgs {
     pub search_term: Option<String>,
     #[clap(
         short = 'n',
         long = "number",
-        default_value_t = 5,
         help = "Change the number of matches printed",
-        long_help = "Change the number of words the program will print. The default value is five."
+        long_help = "Change the number of words the program will print. The default value is five, or the number configuration key if it is set."
     )]
-    pub number: usize,
+    pub number: Option<usize>,
     #[clap(
         short = 'c',
         long = "clean-output",
@@ -31,27 +224,234 @@ pub struct Cli {
         short = 'y',
         long = "yank",
         help = "Yank (copy) to the system cliboard",
-        long_help = "Yank (copy) the selected word to the system clipboard. If no word is selected, the clipboard will not be altered."
+        long_help = "Yank (copy) the selected word to the system clipboard. If no word is selected, the clipboard will not be altered. The clipboard is detected automatically, preferring wl-copy, xclip or xsel when they are installed since they keep serving the text after dym exits, and can be chosen with the clipboard configuration key: native, wl-copy, xclip, xsel, pbcopy, or osc52 to copy through the terminal. Without a display server, such as over SSH, osc52 is used, which requires a terminal supporting OSC 52 and passes through tmux (with allow-passthrough enabled) and GNU Screen. Set the clipboard_selection configuration key to primary to copy to the X11 primary selection instead. On X11 and Wayland the native clipboard is served by a detached background process for at most clipboard_lifetime seconds, one hour by default."
     )]
     pub yank: bool,
     #[clap(
-        short = 'l',
-        long = "lang",
-        help = "Select the desired language using the locale code (en, fr, sp, etc.)",
-        long_help = "Select the desired language using its locale code. For example, English would have the locale code en and French would have the locale code fr. See --print-langs for a list of locale codes and the corresponding languages.",
-        default_value = "en"
+        short = 'p',
+        long = "pick",
+        value_name = "N",
+        help = "Choose the Nth suggestion without a prompt",
+        long_help = "Choose the Nth suggestion instead of prompting for one, and don't print the list of suggestions. The chosen word is printed to standard output unless --yank, --output or --type is given. Exits with status 1 if there are fewer than N suggestions."
     )]
-    pub lang: String,
+    pub pick: Option<usize>,
     #[clap(
-        long = "print-langs",
-        help = "Display a list of supported languages",
-        long_help = "Display a list of supported languages and their respective locale codes."
+        long = "print",
+        help = "Print only the chosen word to standard output",
+        long_help = "Print only the chosen word to standard output. When prompting for a word, the list of suggestions is printed to standard error instead so the output can be captured."
     )]
-    pub print_langs: bool,
+    pub print: bool,
     #[clap(
-        long = "update-langs",
-        help = "Update all language files",
-        long_help = "Update all language files from the repository https://github.com/hisbaan/wordlists."
+        short = 'o',
+        long = "output",
+        value_name = "FILE",
+        help = "Write the chosen word to FILE",
+        long_help = "Write the chosen word to FILE, followed by a newline, replacing its contents."
     )]
-    pub update_langs: bool,
+    pub output: Option<std::path::PathBuf>,
+    #[clap(
+        long = "type",
+        help = "Type the chosen word into the focused window",
+        long_help = "Type the chosen word into the focused window, for binding dym to a hotkey. The word is passed as the last argument of the type_command configuration key, which defaults to `wtype --` on Wayland and `xdotool type --` otherwise."
+    )]
+    pub type_word: bool,
+    #[clap(flatten)]
+    pub lang: LangArgs,
+}
+
+#[derive(Args)]
+pub struct CheckArgs {
+    #[clap(value_name = "FILE", help = "Files to check [default: standard input]")]
+    pub files: Vec<std::path::PathBuf>,
+    #[clap(
+        short = 'f',
+        long = "format",
+        value_name = "FORMAT",
+        value_enum,
+        default_value_t = Format::Quickfix,
+        help = "Print misspelled words in FORMAT",
+        long_help = "Print misspelled words in FORMAT. quickfix (or gcc) prints FILE:LINE:COLUMN: WORD -> SUGGESTIONS lines, read by Vim's quickfix list and editors parsing compiler messages. checkstyle prints Checkstyle XML and sarif prints SARIF 2.1.0 JSON, for CI tools and code scanning."
+    )]
+    pub format: Format,
+    #[clap(
+        short = 'n',
+        long = "number",
+        default_value_t = 3,
+        help = "Change the number of suggestions printed for each misspelled word"
+    )]
+    pub number: usize,
+    #[clap(flatten)]
+    pub lang: LangArgs,
+}
+
+#[derive(Args)]
+pub struct InteractiveArgs {
+    #[clap(value_name = "QUERY", help = "Start with QUERY typed in")]
+    pub query: Option<String>,
+    #[clap(
+        short = 'n',
+        long = "number",
+        help = "Change the number of suggestions shown",
+        long_help = "Change the number of suggestions shown. The default value is five, or the number configuration key if it is set."
+    )]
+    pub number: Option<usize>,
+    #[clap(flatten)]
+    pub lang: LangArgs,
+}
+
+#[derive(Args)]
+pub struct ReplArgs {
+    #[clap(
+        short = 'n',
+        long = "number",
+        help = "Change the number of matches printed",
+        long_help = "Change the number of words printed for each query. The default value is five, or the number configuration key if it is set. It can be changed during the session with `:n N`."
+    )]
+    pub number: Option<usize>,
+    #[clap(
+        short = 'c',
+        long = "clean-output",
+        help = "Print clean output",
+        long_help = "Print a clean version of the output without the title, numbers or colour."
+    )]
+    pub clean_output: bool,
+    #[clap(
+        short = 'v',
+        long = "verbose",
+        help = "Print verbose output",
+        long_help = "Print verbose output including the edit distance of the found word to the queried word."
+    )]
+    pub verbose: bool,
+    #[clap(flatten)]
+    pub lang: LangArgs,
+}
+
+#[derive(Subcommand)]
+pub enum LangCommand {
+    #[clap(
+        about = "Display a list of supported languages",
+        long_about = "Display a list of supported languages and their respective locale codes."
+    )]
+    List {
+        #[clap(
+            short = 'i',
+            long = "installed",
+            help = "Only display installed languages",
+            long_help = "Only display installed languages, along with the size and modification date of their word lists. Word lists preinstalled in a system-wide directory are marked as such."
+        )]
+        installed: bool,
+    },
+    #[clap(
+        about = "Download word lists",
+        long_about = "Download the word lists of the given languages so they are available without waiting for a download on first use. Languages that are already installed are skipped."
+    )]
+    Install {
+        #[clap(value_name = "LANG", required = true)]
+        langs: Vec<String>,
+    },
+    #[clap(
+        about = "Update all language files",
+        long_about = "Update all downloaded language files from the repository https://github.com/hisbaan/wordlists. Only word lists that changed since they were downloaded are fetched again, and a word list is kept if its update fails. Exits with status 1 if any update failed."
+    )]
+    Update,
+    #[clap(
+        about = "Delete downloaded word lists",
+        long_about = "Delete the downloaded word lists of the given languages. Word lists preinstalled in a system-wide directory can't be removed."
+    )]
+    Remove {
+        #[clap(value_name = "LANG", required = true)]
+        langs: Vec<String>,
+    },
+    #[clap(
+        about = "Pack installed language files into a bundle",
+        long_about = "Pack the installed word lists of the given languages, or all installed word lists if none are given, into a single archive along with their checksums. The archive can be copied to machines without network access and installed there with `dym lang import`."
+    )]
+    Export {
+        #[clap(value_name = "FILE")]
+        file: std::path::PathBuf,
+        #[clap(value_name = "LANG")]
+        langs: Vec<String>,
+    },
+    #[clap(
+        about = "Install language files from a bundle",
+        long_about = "Install the word lists in an archive created with `dym lang export` into the data directory, replacing installed copies. Every word list is checked against its checksum first, and nothing is installed if any of them is invalid."
+    )]
+    Import {
+        #[clap(value_name = "FILE")]
+        file: std::path::PathBuf,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GitHookCommand {
+    #[clap(
+        about = "Check a commit message",
+        long_about = "Check the commit message in FILE for words that are not in the word list, as the commit-msg hook of git, and print suggestions for each of them. Comment lines, trailers such as Signed-off-by and the diff added by `git commit --verbose` are skipped. Words in the .dym-words file at the top of the repository are known in addition to the personal word list. Misspelled words only abort the commit with --strict."
+    )]
+    CommitMsg {
+        #[clap(value_name = "FILE")]
+        file: std::path::PathBuf,
+        #[clap(
+            long = "strict",
+            help = "Exit with status 1 if any misspelled words are found, aborting the commit"
+        )]
+        strict: bool,
+        #[clap(
+            short = 'n',
+            long = "number",
+            default_value_t = 3,
+            help = "Change the number of suggestions printed for each misspelled word"
+        )]
+        number: usize,
+        #[clap(flatten)]
+        lang: LangArgs,
+    },
+    #[clap(
+        about = "Install the commit-msg hook in the current repository",
+        long_about = "Install a commit-msg hook running `dym git-hook commit-msg` in the hooks directory of the current repository. An existing hook that wasn't installed by dym is only replaced with --force."
+    )]
+    Install {
+        #[clap(long = "strict", help = "Abort commits with misspelled words")]
+        strict: bool,
+        #[clap(long = "force", help = "Replace an existing commit-msg hook")]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum DictCommand {
+    #[clap(about = "Add words to your personal word list")]
+    Add {
+        #[clap(required = true)]
+        words: Vec<String>,
+        #[clap(flatten)]
+        lang: LangArgs,
+    },
+    #[clap(about = "Remove words from your personal word list")]
+    Remove {
+        #[clap(required = true)]
+        words: Vec<String>,
+        #[clap(flatten)]
+        lang: LangArgs,
+    },
+    #[clap(about = "Print your personal word list")]
+    List {
+        #[clap(flatten)]
+        lang: LangArgs,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    #[clap(
+        about = "Print the path of the configuration file",
+        long_about = "Print the path of the configuration file. This can be changed with the DYM_CONFIG environment variable."
+    )]
+    Path,
+    #[clap(about = "Print the configuration with defaults filled in")]
+    Show,
+    #[clap(about = "Set a configuration key")]
+    Set { key: String, value: String },
+    #[clap(about = "Remove a configuration key, restoring its default")]
+    Unset { key: String },
 }
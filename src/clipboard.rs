@@ -0,0 +1,553 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    env,
+    io::{Error, ErrorKind, Write},
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+/// The length of each piece an OSC 52 sequence is split into to pass through GNU Screen, which
+/// truncates longer passthrough sequences.
+const SCREEN_CHUNK_SIZE: usize = 76;
+
+/// How long the native clipboard is served when the `clipboard_lifetime` key isn't set.
+pub const DEFAULT_LIFETIME: Duration = Duration::from_secs(60 * 60);
+
+/// How often the native clipboard server checks whether the clipboard was taken over.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A way of copying text to the clipboard.
+pub trait ClipboardBackend {
+    /// Return the name of the backend, as used in the `clipboard` configuration key.
+    fn name(&self) -> &'static str;
+
+    /// Copy `text` to the clipboard.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to copy.
+    /// * `options` - The selection to copy to and how long to serve it for.
+    fn copy(&self, text: &str, options: &Options) -> Result<(), Error>;
+}
+
+/// The X11 selection text is copied to. Wayland compositors supporting the primary selection
+/// protocol treat it the same way.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Selection {
+    /// The clipboard, pasted with Ctrl+V.
+    #[default]
+    Clipboard,
+    /// The primary selection, pasted with the middle mouse button.
+    Primary,
+}
+
+/// Settings shared by every clipboard backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Options {
+    /// The selection to copy to.
+    pub selection: Selection,
+    /// The longest time the native clipboard is kept alive for on X11 and Wayland, where the
+    /// process that copied the text has to serve it to other programs. Clipboard commands such
+    /// as `wl-copy` serve the text themselves until it is replaced.
+    pub lifetime: Duration,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            selection: Selection::Clipboard,
+            lifetime: DEFAULT_LIFETIME,
+        }
+    }
+}
+
+/// The clipboard backends that can be chosen in the configuration.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+    /// Pick the first backend that works on this system.
+    #[default]
+    Auto,
+    /// The platform clipboard API.
+    Native,
+    /// The `wl-copy` command of Wayland.
+    WlCopy,
+    /// The `xclip` command of X11.
+    Xclip,
+    /// The `xsel` command of X11.
+    Xsel,
+    /// The `pbcopy` command of macOS.
+    Pbcopy,
+    /// OSC 52 terminal escape sequences.
+    Osc52,
+}
+
+impl Backend {
+    /// Return the clipboard backend to copy with. Without a display server, such as over SSH,
+    /// `Auto` copies through the terminal with OSC 52. Otherwise it prefers the clipboard command
+    /// for the running display server, which keeps serving the text after dym exits, then the
+    /// platform clipboard, falling back to OSC 52. Return a `NotFound` error if the chosen
+    /// backend isn't available.
+    pub fn select(self) -> Result<Box<dyn ClipboardBackend>, Error> {
+        match self {
+            Backend::Auto => {
+                if !has_display() {
+                    return Ok(Box::new(Osc52::new()));
+                }
+                if let Some(command) = CommandBackend::ALL
+                    .into_iter()
+                    .find(|command| command.is_usable() && command.is_available())
+                {
+                    return Ok(Box::new(command));
+                }
+                if Native::is_available() {
+                    return Ok(Box::new(Native));
+                }
+
+                Ok(Box::new(Osc52::new()))
+            }
+            Backend::Native => Ok(Box::new(Native)),
+            Backend::WlCopy => CommandBackend::WL_COPY.require(),
+            Backend::Xclip => CommandBackend::XCLIP.require(),
+            Backend::Xsel => CommandBackend::XSEL.require(),
+            Backend::Pbcopy => CommandBackend::PBCOPY.require(),
+            Backend::Osc52 => Ok(Box::new(Osc52::new())),
+        }
+    }
+}
+
+/// Copy `text` to the clipboard using `backend`.
+///
+/// # Arguments
+///
+/// * `text` - The text to copy.
+/// * `backend` - The clipboard backend to use.
+/// * `options` - The selection to copy to and how long to serve it for.
+pub fn yank(text: &str, backend: Backend, options: &Options) -> Result<(), Error> {
+    backend.select()?.copy(text, options)
+}
+
+/// The platform clipboard API, through the `cli-clipboard` crate.
+#[derive(Debug, Clone, Copy)]
+pub struct Native;
+
+impl Native {
+    /// Return whether the platform clipboard can be opened, which fails on systems without a
+    /// display server.
+    pub fn is_available() -> bool {
+        use cli_clipboard::{ClipboardContext, ClipboardProvider};
+
+        <ClipboardContext as ClipboardProvider>::new().is_ok()
+    }
+}
+
+impl ClipboardBackend for Native {
+    fn name(&self) -> &'static str {
+        "native"
+    }
+
+    fn copy(&self, text: &str, options: &Options) -> Result<(), Error> {
+        // On X11 and Wayland the clipboard is owned by a process, and is cleared when that process
+        // exits, so a detached process is left behind to serve it.
+        #[cfg(all(unix, not(any(target_os = "macos", target_os = "android"))))]
+        {
+            use cli_clipboard::{
+                x11_clipboard::{Primary, X11ClipboardContext},
+                ClipboardContext,
+            };
+
+            // Only X11 has a primary selection in the platform clipboard API. Wayland sessions
+            // reach it through XWayland.
+            match options.selection {
+                Selection::Clipboard => serve::<ClipboardContext>(text, options.lifetime),
+                Selection::Primary => serve::<X11ClipboardContext<Primary>>(text, options.lifetime),
+            }
+        }
+
+        // Other platforms keep the clipboard contents after dym exits.
+        #[cfg(not(all(unix, not(any(target_os = "macos", target_os = "android")))))]
+        {
+            use cli_clipboard::{ClipboardContext, ClipboardProvider};
+
+            if options.selection == Selection::Primary {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "The primary selection is only available on X11 and Wayland",
+                ));
+            }
+            let mut ctx: ClipboardContext =
+                ClipboardProvider::new().map_err(|error| Error::other(error.to_string()))?;
+            ctx.set_contents(text.to_owned())
+                .map_err(|error| Error::other(error.to_string()))
+        }
+    }
+}
+
+/// Set the clipboard to `text` from a detached process that serves it to other programs until
+/// the clipboard is taken over or `lifetime` has passed. Return once the clipboard is set, or
+/// with the error that prevented setting it.
+///
+/// The process is forked twice and starts a new session, so it isn't killed along with the
+/// terminal and never becomes a zombie. Its standard streams are redirected to `/dev/null` so it
+/// doesn't hold on to pipes dym's output is read from.
+///
+/// # Arguments
+///
+/// * `text` - The text to copy.
+/// * `lifetime` - The longest time to serve the clipboard for.
+#[cfg(all(unix, not(any(target_os = "macos", target_os = "android"))))]
+fn serve<C: cli_clipboard::ClipboardProvider>(text: &str, lifetime: Duration) -> Result<(), Error> {
+    use nix::{
+        sys::wait::waitpid,
+        unistd::{dup2, fork, setsid, ForkResult},
+    };
+    use std::{
+        fs::OpenOptions,
+        io::Read,
+        os::{fd::AsRawFd, unix::net::UnixStream},
+        process::exit,
+        time::Instant,
+    };
+
+    // The server reports whether it set the clipboard through this socket.
+    let (mut parent, mut child) = UnixStream::pair()?;
+
+    match unsafe { fork() }.map_err(Error::from)? {
+        ForkResult::Parent { child: pid } => {
+            drop(child);
+            waitpid(pid, None).map_err(Error::from)?;
+
+            let mut report = String::new();
+            parent.read_to_string(&mut report)?;
+            match report.strip_prefix("ok") {
+                Some("") => Ok(()),
+                _ if report.is_empty() => {
+                    Err(Error::other("The clipboard process exited unexpectedly"))
+                }
+                _ => Err(Error::other(report)),
+            }
+        }
+        ForkResult::Child => {
+            drop(parent);
+            // Leave the terminal's session, then fork again so the server can't reacquire a
+            // controlling terminal and is adopted by init once the intermediate process exits.
+            if setsid().is_err() {
+                exit(1);
+            }
+            match unsafe { fork() } {
+                Ok(ForkResult::Child) => {}
+                _ => exit(0),
+            }
+
+            let mut ctx = match C::new() {
+                Ok(ctx) => ctx,
+                Err(error) => {
+                    child.write_all(error.to_string().as_bytes()).ok();
+                    exit(1);
+                }
+            };
+            if let Err(error) = ctx.set_contents(text.to_owned()) {
+                child.write_all(error.to_string().as_bytes()).ok();
+                exit(1);
+            }
+
+            let detached = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .open("/dev/null")
+                .and_then(|null| {
+                    for fd in 0..=2 {
+                        dup2(null.as_raw_fd(), fd).map_err(Error::from)?;
+                    }
+                    Ok(())
+                });
+            if detached.is_err() || child.write_all(b"ok").is_err() {
+                exit(1);
+            }
+            drop(child);
+
+            // Serve the clipboard until another program replaces it.
+            let start = Instant::now();
+            while start.elapsed() < lifetime {
+                std::thread::sleep(POLL_INTERVAL);
+                match ctx.get_contents() {
+                    Ok(contents) if contents == text => {}
+                    _ => break,
+                }
+            }
+            exit(0)
+        }
+    }
+}
+
+/// A command reading the text to copy from its standard input.
+#[derive(Debug, Clone, Copy)]
+pub struct CommandBackend {
+    program: &'static str,
+    args: &'static [&'static str],
+    /// Arguments copying to the primary selection instead, if the command supports it.
+    primary_args: Option<&'static [&'static str]>,
+    /// Environment variable that is set when the display server the command works with runs.
+    display: Option<&'static str>,
+}
+
+impl CommandBackend {
+    /// `wl-copy`, for Wayland.
+    pub const WL_COPY: CommandBackend = CommandBackend {
+        program: "wl-copy",
+        args: &[],
+        primary_args: Some(&["--primary"]),
+        display: Some("WAYLAND_DISPLAY"),
+    };
+
+    /// `xclip`, for X11.
+    pub const XCLIP: CommandBackend = CommandBackend {
+        program: "xclip",
+        args: &["-selection", "clipboard"],
+        primary_args: Some(&["-selection", "primary"]),
+        display: Some("DISPLAY"),
+    };
+
+    /// `xsel`, for X11.
+    pub const XSEL: CommandBackend = CommandBackend {
+        program: "xsel",
+        args: &["--clipboard", "--input"],
+        primary_args: Some(&["--primary", "--input"]),
+        display: Some("DISPLAY"),
+    };
+
+    /// `pbcopy`, for macOS.
+    pub const PBCOPY: CommandBackend = CommandBackend {
+        program: "pbcopy",
+        args: &[],
+        primary_args: None,
+        display: None,
+    };
+
+    /// Every clipboard command, in the order they are tried by `Backend::Auto`.
+    pub const ALL: [CommandBackend; 4] = [
+        CommandBackend::WL_COPY,
+        CommandBackend::XCLIP,
+        CommandBackend::XSEL,
+        CommandBackend::PBCOPY,
+    ];
+
+    /// Return whether the command is installed in a directory on the `PATH`.
+    pub fn is_available(&self) -> bool {
+        env::var_os("PATH")
+            .is_some_and(|path| env::split_paths(&path).any(|dir| dir.join(self.program).is_file()))
+    }
+
+    /// Return whether the display server the command works with is running. Commands that don't
+    /// need a display server are always usable.
+    pub fn is_usable(&self) -> bool {
+        self.display
+            .is_none_or(|name| env::var_os(name).is_some_and(|value| !value.is_empty()))
+    }
+
+    /// Return the command as a backend, or a `NotFound` error if it isn't installed.
+    fn require(self) -> Result<Box<dyn ClipboardBackend>, Error> {
+        if self.is_available() {
+            Ok(Box::new(self))
+        } else {
+            Err(Error::new(
+                ErrorKind::NotFound,
+                format!("{} is not installed", self.program),
+            ))
+        }
+    }
+}
+
+impl ClipboardBackend for CommandBackend {
+    fn name(&self) -> &'static str {
+        self.program
+    }
+
+    fn copy(&self, text: &str, options: &Options) -> Result<(), Error> {
+        let args = match options.selection {
+            Selection::Clipboard => self.args,
+            Selection::Primary => self.primary_args.ok_or_else(|| {
+                Error::new(
+                    ErrorKind::Unsupported,
+                    format!("{} can't copy to the primary selection", self.program),
+                )
+            })?,
+        };
+
+        // The commands keep running in the background to serve the clipboard, so they must not
+        // hold on to our output.
+        let mut child = Command::new(self.program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        child.stdin.take().unwrap().write_all(text.as_bytes())?;
+
+        let status = child.wait()?;
+        if !status.success() {
+            return Err(Error::other(format!(
+                "{} failed with {}",
+                self.program, status
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// A terminal multiplexer escape sequences have to be passed through to reach the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Multiplexer {
+    /// The escape sequence is written to the terminal directly.
+    None,
+    /// tmux, which only passes escape sequences through with `set -g allow-passthrough on`.
+    Tmux,
+    /// GNU Screen.
+    Screen,
+}
+
+impl Multiplexer {
+    /// Detect the multiplexer dym is running in from the environment variables it sets.
+    pub fn detect() -> Multiplexer {
+        let is_set = |name| env::var_os(name).is_some_and(|value| !value.is_empty());
+
+        if is_set("TMUX") {
+            Multiplexer::Tmux
+        } else if is_set("STY") {
+            Multiplexer::Screen
+        } else {
+            Multiplexer::None
+        }
+    }
+}
+
+/// OSC 52 terminal escape sequences, which ask the terminal emulator to set the clipboard. This
+/// works over SSH and without a display server, as long as the terminal supports it.
+#[derive(Debug, Clone, Copy)]
+pub struct Osc52 {
+    multiplexer: Multiplexer,
+}
+
+impl Osc52 {
+    /// Create an OSC 52 backend for the multiplexer dym is running in.
+    pub fn new() -> Osc52 {
+        Osc52 {
+            multiplexer: Multiplexer::detect(),
+        }
+    }
+
+    /// Return the escape sequence setting `selection` to `text`, wrapped so it passes through
+    /// `multiplexer`.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The text to copy.
+    /// * `selection` - The selection to copy to.
+    /// * `multiplexer` - The multiplexer the sequence has to pass through.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use didyoumean::clipboard::{Multiplexer, Osc52, Selection};
+    /// assert_eq!(
+    ///     Osc52::sequence("hello", Selection::Clipboard, Multiplexer::None),
+    ///     "\x1b]52;c;aGVsbG8=\x07"
+    /// );
+    /// assert_eq!(
+    ///     Osc52::sequence("hello", Selection::Primary, Multiplexer::None),
+    ///     "\x1b]52;p;aGVsbG8=\x07"
+    /// );
+    /// assert_eq!(
+    ///     Osc52::sequence("hello", Selection::Clipboard, Multiplexer::Tmux),
+    ///     "\x1bPtmux;\x1b\x1b]52;c;aGVsbG8=\x07\x1b\\"
+    /// );
+    /// assert_eq!(
+    ///     Osc52::sequence("hello", Selection::Clipboard, Multiplexer::Screen),
+    ///     "\x1bP\x1b]52;c;aGVsbG8=\x07\x1b\\"
+    /// );
+    /// ```
+    pub fn sequence(text: &str, selection: Selection, multiplexer: Multiplexer) -> String {
+        let target = match selection {
+            Selection::Clipboard => 'c',
+            Selection::Primary => 'p',
+        };
+        let sequence = format!("\x1b]52;{};{}\x07", target, base64(text.as_bytes()));
+
+        match multiplexer {
+            Multiplexer::None => sequence,
+            // tmux requires escape characters inside the passthrough to be doubled.
+            Multiplexer::Tmux => {
+                format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+            }
+            // Screen limits the length of each passthrough, so the sequence is split up.
+            Multiplexer::Screen => sequence
+                .as_bytes()
+                .chunks(SCREEN_CHUNK_SIZE)
+                .map(|chunk| format!("\x1bP{}\x1b\\", String::from_utf8_lossy(chunk)))
+                .collect(),
+        }
+    }
+}
+
+impl Default for Osc52 {
+    fn default() -> Osc52 {
+        Osc52::new()
+    }
+}
+
+impl ClipboardBackend for Osc52 {
+    fn name(&self) -> &'static str {
+        "osc52"
+    }
+
+    fn copy(&self, text: &str, options: &Options) -> Result<(), Error> {
+        let sequence = Osc52::sequence(text, options.selection, self.multiplexer);
+
+        // Write to the terminal directly so the sequence isn't captured by a pipe.
+        #[cfg(unix)]
+        let mut terminal = std::fs::OpenOptions::new().write(true).open("/dev/tty")?;
+        #[cfg(not(unix))]
+        let mut terminal = std::io::stdout();
+
+        terminal.write_all(sequence.as_bytes())?;
+        terminal.flush()
+    }
+}
+
+/// Return whether a display server with a clipboard is available. Linux and the BSDs need an X11
+/// or Wayland display, which remote sessions over SSH usually don't have.
+pub fn has_display() -> bool {
+    if cfg!(any(target_os = "macos", target_os = "windows")) {
+        return true;
+    }
+
+    ["DISPLAY", "WAYLAND_DISPLAY"]
+        .iter()
+        .any(|name| env::var_os(name).is_some_and(|value| !value.is_empty()))
+}
+
+/// Encode `bytes` as standard base64 with padding.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode.
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(group >> (18 - 6 * i) & 0x3f) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
@@ -0,0 +1,219 @@
+use colored::*;
+use dialoguer::{theme::ColorfulTheme, Select};
+use std::{
+    ffi::OsStr,
+    fs::read_dir,
+    io::{self, Error, Write},
+    path::Path,
+};
+
+use crate::{format_suggestions, write_heading};
+use didyoumean::dictionary::{Dictionary, Suggestion};
+
+/// The exit status shells use for commands that weren't found.
+pub const NOT_FOUND: i32 = 127;
+
+/// Suggest commands close to `name`, the name of a command that wasn't found. Commands are the
+/// executables on `$PATH` and the given aliases. When `prompt` is set and standard error is a
+/// terminal, the user chooses one of the suggestions, which is printed to standard output so the
+/// shell can run it. Everything else is printed to standard error. Return 127 unless a command was
+/// chosen.
+///
+/// # Arguments
+///
+/// * `name` - The name of the command that wasn't found.
+/// * `aliases` - Aliases and functions of the shell, separated by whitespace.
+/// * `number` - The maximum number of suggestions.
+/// * `prompt` - Whether to let the user choose a command to run instead.
+pub fn run(name: &str, aliases: &[String], number: usize, prompt: bool) -> Result<i32, Error> {
+    // Aliases come first so they win ties with executables, since the user defined them.
+    let mut dictionary = Dictionary::new();
+    dictionary.extend(aliases.iter().flat_map(|alias| alias.split_whitespace()));
+    if let Some(path) = std::env::var_os("PATH") {
+        for dir in std::env::split_paths(&path) {
+            dictionary.extend(executables(&dir).iter().map(String::as_str));
+        }
+    }
+
+    let suggestions = close_commands(&dictionary, name, number);
+
+    let mut out = io::stderr();
+    writeln!(out, "{}", format!("{}: command not found", name).red())?;
+    if suggestions.is_empty() {
+        return Ok(NOT_FOUND);
+    }
+    write_heading(&mut out, name, &suggestions, false)?;
+    let items = format_suggestions(&suggestions, number, false, false);
+
+    if !prompt || !atty::is(atty::Stream::Stderr) {
+        for item in items {
+            writeln!(out, "{}", item)?;
+        }
+        return Ok(NOT_FOUND);
+    }
+
+    writeln!(
+        out,
+        "{} {}",
+        "?".yellow(),
+        "[↑↓ to move, ↵ to run, esc/q to cancel]".bold()
+    )?;
+    let chosen = Select::with_theme(&ColorfulTheme::default())
+        .items(&items)
+        .default(0)
+        .interact_opt()
+        .map_err(|dialoguer::Error::IO(error)| error)?;
+
+    match chosen {
+        Some(index) => {
+            println!("{}", suggestions[index].word);
+            Ok(0)
+        }
+        None => Ok(NOT_FOUND),
+    }
+}
+
+/// Return the suggestions for `name` that are close enough to be likely typos. Allowing a third
+/// of the characters to differ keeps short names from matching every other short command.
+///
+/// # Arguments
+///
+/// * `dictionary` - The names of the known commands.
+/// * `name` - The name of the command that wasn't found.
+/// * `number` - The maximum number of suggestions.
+fn close_commands<'a>(
+    dictionary: &'a Dictionary,
+    name: &str,
+    number: usize,
+) -> Vec<Suggestion<'a>> {
+    let max_distance = (name.chars().count() / 3).max(1);
+    dictionary
+        .suggest(name, number)
+        .into_iter()
+        .filter(|suggestion| suggestion.distance <= max_distance)
+        .collect()
+}
+
+/// Return the names of the executables in `dir`. Unreadable directories are skipped.
+///
+/// # Arguments
+///
+/// * `dir` - A directory of `$PATH`.
+fn executables(dir: &Path) -> Vec<String> {
+    let Ok(entries) = read_dir(dir) else {
+        return vec![];
+    };
+
+    entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            // Follow symbolic links, which are how many executables are installed.
+            let metadata = path.metadata().ok()?;
+            if !metadata.is_file() || !is_executable(&path, &metadata) {
+                return None;
+            }
+            let name = if cfg!(windows) {
+                path.file_stem()
+            } else {
+                path.file_name()
+            };
+            name.and_then(OsStr::to_str).map(str::to_owned)
+        })
+        .collect()
+}
+
+/// Return whether the file at `path` can be executed.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+/// * `metadata` - The metadata of the file.
+#[cfg(unix)]
+fn is_executable(_path: &Path, metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+/// Return whether the file at `path` can be executed, going by the extensions in `PATHEXT`.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+/// * `metadata` - The metadata of the file.
+#[cfg(not(unix))]
+fn is_executable(path: &Path, _metadata: &std::fs::Metadata) -> bool {
+    let extensions = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_owned());
+    let Some(extension) = path.extension().and_then(OsStr::to_str) else {
+        return false;
+    };
+    extensions.split(';').any(|known| {
+        known
+            .trim_start_matches('.')
+            .eq_ignore_ascii_case(extension)
+    })
+}
+
+/// Return the snippet installing dym as the command not found handler of `shell`. With `prompt`,
+/// the handler asks which suggestion to run and runs it with the original arguments. Aliases
+/// aren't expanded when the chosen name is run from a variable, so only functions are suggested
+/// besides executables then.
+///
+/// # Arguments
+///
+/// * `shell` - The shell to write the snippet for: bash, zsh or fish.
+/// * `prompt` - Whether the handler lets the user run a suggestion.
+pub fn hook(shell: &str, prompt: bool) -> &'static str {
+    match (shell, prompt) {
+        ("bash", false) => {
+            "\
+command_not_found_handle() {
+    dym cmd --alias \"$(compgen -a -A function)\" -- \"$1\"
+}
+"
+        }
+        ("bash", true) => {
+            "\
+command_not_found_handle() {
+    local fixed
+    fixed=$(dym cmd --prompt --alias \"$(compgen -A function)\" -- \"$1\") || return 127
+    shift
+    \"$fixed\" \"$@\"
+}
+"
+        }
+        ("zsh", false) => {
+            "\
+command_not_found_handler() {
+    dym cmd --alias \"${(k)aliases} ${(k)functions}\" -- \"$1\"
+}
+"
+        }
+        ("zsh", true) => {
+            "\
+command_not_found_handler() {
+    local fixed
+    fixed=$(dym cmd --prompt --alias \"${(k)functions}\" -- \"$1\") || return 127
+    shift
+    \"$fixed\" \"$@\"
+}
+"
+        }
+        ("fish", false) => {
+            "\
+function fish_command_not_found
+    dym cmd --alias (functions --names | string join ' ') -- $argv[1]
+end
+"
+        }
+        ("fish", true) => {
+            "\
+function fish_command_not_found
+    set -l fixed (dym cmd --prompt --alias (functions --names | string join ' ') -- $argv[1])
+    or return 127
+    $fixed $argv[2..-1]
+end
+"
+        }
+        _ => unreachable!("clap only accepts bash, zsh and fish"),
+    }
+}
@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::read,
+    io::{Error, ErrorKind, Write},
+    path::Path,
+};
+
+/// Magic bytes at the start of a gzip stream.
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// Magic bytes at the start of a Zstandard frame.
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// A compression format word lists can be downloaded or stored in.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    /// Plain text.
+    #[default]
+    None,
+    /// gzip, stored with the `.gz` extension.
+    Gzip,
+    /// Zstandard, stored with the `.zst` extension.
+    Zstd,
+}
+
+impl Compression {
+    /// Detect the compression of `bytes` from their magic bytes. Word lists are UTF-8 text, which
+    /// can't start with either magic, so plain word lists are never mistaken for compressed ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The start of a word list file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use didyoumean::compression::Compression;
+    /// assert_eq!(Compression::detect(b"hello\n"), Compression::None);
+    /// assert_eq!(Compression::detect(&[0x1f, 0x8b, 0x08]), Compression::Gzip);
+    /// ```
+    pub fn detect(bytes: &[u8]) -> Compression {
+        if bytes.starts_with(GZIP_MAGIC) {
+            Compression::Gzip
+        } else if bytes.starts_with(ZSTD_MAGIC) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Return the file extension of the format, including the leading dot.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Compression::None => "",
+            Compression::Gzip => ".gz",
+            Compression::Zstd => ".zst",
+        }
+    }
+
+    /// Compress `bytes` with this format.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The data to compress.
+    pub fn compress(&self, bytes: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            Compression::None => Ok(bytes.to_vec()),
+            Compression::Gzip => {
+                let mut encoder =
+                    flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::best());
+                encoder.write_all(bytes)?;
+                encoder.finish()
+            }
+            Compression::Zstd => zstd::encode_all(bytes, 19),
+        }
+    }
+
+    /// Return a writer decompressing this format into `writer`.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - The writer to write the decompressed data to.
+    pub fn decoder<W: Write>(&self, writer: W) -> Result<Decoder<W>, Error> {
+        Ok(match self {
+            Compression::None => Decoder::None(writer),
+            Compression::Gzip => Decoder::Gzip(flate2::write::GzDecoder::new(writer)),
+            Compression::Zstd => Decoder::Zstd(zstd::stream::write::Decoder::new(writer)?),
+        })
+    }
+}
+
+/// A writer decompressing the data written to it, created by `Compression::decoder`.
+pub enum Decoder<W: Write> {
+    None(W),
+    Gzip(flate2::write::GzDecoder<W>),
+    Zstd(zstd::stream::write::Decoder<'static, W>),
+}
+
+impl<W: Write> Decoder<W> {
+    /// Finish decompressing and return the underlying writer.
+    pub fn finish(self) -> Result<W, Error> {
+        match self {
+            Decoder::None(writer) => Ok(writer),
+            Decoder::Gzip(decoder) => decoder.finish(),
+            Decoder::Zstd(mut decoder) => {
+                decoder.flush()?;
+                Ok(decoder.into_inner())
+            }
+        }
+    }
+}
+
+impl<W: Write> Write for Decoder<W> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        match self {
+            Decoder::None(writer) => writer.write(buf),
+            Decoder::Gzip(decoder) => decoder.write(buf),
+            Decoder::Zstd(decoder) => decoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        match self {
+            Decoder::None(writer) => writer.flush(),
+            Decoder::Gzip(decoder) => decoder.flush(),
+            Decoder::Zstd(decoder) => decoder.flush(),
+        }
+    }
+}
+
+/// Decompress `bytes` if they are compressed, detecting the format from their magic bytes.
+///
+/// # Arguments
+///
+/// * `bytes` - The contents of a word list file.
+///
+/// # Examples
+///
+/// ```
+/// # use didyoumean::compression::{decompress, Compression};
+/// let compressed = Compression::Zstd.compress(b"hello\n").unwrap();
+/// assert_eq!(decompress(compressed).unwrap(), b"hello\n");
+/// assert_eq!(decompress(b"hello\n".to_vec()).unwrap(), b"hello\n");
+/// ```
+pub fn decompress(bytes: Vec<u8>) -> Result<Vec<u8>, Error> {
+    match Compression::detect(&bytes) {
+        Compression::None => Ok(bytes),
+        compression => {
+            let mut decoder = compression.decoder(Vec::new())?;
+            decoder
+                .write_all(&bytes)
+                .and_then(|_| decoder.finish())
+                .map_err(|error| Error::new(ErrorKind::InvalidData, error))
+        }
+    }
+}
+
+/// Read the word list file at `path`, decompressing it if it is stored compressed. Return an
+/// `InvalidData` error if it can't be decompressed or isn't valid UTF-8.
+///
+/// # Arguments
+///
+/// * `path` - The path of a word list file.
+pub fn read_word_list(path: &Path) -> Result<String, Error> {
+    String::from_utf8(decompress(read(path)?)?).map_err(|error| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("{} is not valid UTF-8: {}", path.display(), error),
+        )
+    })
+}
@@ -0,0 +1,314 @@
+#[cfg(feature = "clipboard")]
+use didyoumean::clipboard::{self, Backend, Selection};
+use didyoumean::{compression::Compression, data::DataDirs};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    env,
+    fs::{create_dir_all, read_to_string, write},
+    io::{Error, ErrorKind},
+    path::PathBuf,
+    time::Duration,
+};
+
+/// Environment variable used to override the path of the configuration file.
+pub const CONFIG_ENV: &str = "DYM_CONFIG";
+
+/// Environment variable used to override the `source_url` key.
+pub const SOURCE_URL_ENV: &str = "DYM_SOURCE_URL";
+
+/// Environment variable used to override the `mirrors` key, as a whitespace separated list.
+pub const MIRRORS_ENV: &str = "DYM_MIRRORS";
+
+/// The language used when neither `--lang` nor the `lang` key is set.
+pub const DEFAULT_LANG: &str = "en";
+
+/// The number of suggestions printed when neither `--number` nor the `number` key is set.
+pub const DEFAULT_NUMBER: usize = 5;
+
+/// The number of seconds to wait for a connection or for data when the `timeout` key isn't set.
+pub const DEFAULT_TIMEOUT: u64 = 30;
+
+/// The largest number of suggestions a client of a long-running command may ask for.
+pub const MAX_NUMBER: usize = 1000;
+
+/// The number of times a failed download is retried when the `retries` key isn't set.
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// The name of the daemon's socket in the user data directory when the `socket` key isn't set.
+pub const DEFAULT_SOCKET: &str = "daemon.sock";
+
+/// The command typing words for `--type` on Wayland when the `type_command` key isn't set.
+pub const DEFAULT_WAYLAND_TYPE_COMMAND: &str = "wtype --";
+
+/// The command typing words for `--type` elsewhere when the `type_command` key isn't set.
+pub const DEFAULT_TYPE_COMMAND: &str = "xdotool type --";
+
+/// A command in the configuration file, either a string split into arguments the way a shell
+/// would, or an array of arguments.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum CommandLine {
+    Line(String),
+    Args(Vec<String>),
+}
+
+impl CommandLine {
+    /// Return the program and arguments of the command.
+    pub fn args(&self) -> Result<Vec<String>, Error> {
+        match self {
+            CommandLine::Line(line) => shell_words::split(line).map_err(|error| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid command {}: {}", line, error),
+                )
+            }),
+            CommandLine::Args(args) => Ok(args.clone()),
+        }
+    }
+}
+
+/// Settings read from `config.toml` in the platform configuration directory.
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Locale code of the default language.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lang: Option<String>,
+    /// Default number of suggestions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<usize>,
+    /// Directory downloaded word lists are stored in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_dir: Option<PathBuf>,
+    /// Base URL of the word list repository. `file://` URLs are also supported.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_url: Option<String>,
+    /// Base URLs tried in order when downloading from `source_url` fails.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mirrors: Option<Vec<String>>,
+    /// Seconds to wait for a connection or for data before a download fails.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    /// Number of times a download is retried after a network error.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<u32>,
+    /// Proxy used for all downloads instead of the `HTTPS_PROXY` and `HTTP_PROXY` environment
+    /// variables. Hosts listed in `NO_PROXY` are still contacted directly.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<String>,
+    /// Format downloaded word lists are stored in: `none`, `gzip` or `zstd`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compression: Option<Compression>,
+    /// Unix domain socket `dym daemon` listens on and other commands query.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub socket: Option<PathBuf>,
+    /// Hunspell dictionaries used for languages without a word list, such as `pt_BR` for `pt`, by
+    /// locale code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hunspell: Option<BTreeMap<String, String>>,
+    /// Origins of the web pages allowed to call `dym serve`, or `*` for any page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cors_origins: Option<Vec<String>>,
+    /// Command typing the word chosen with `--type`, which is passed as its last argument.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub type_command: Option<CommandLine>,
+    /// Clipboard used by `--yank`: `auto`, `native`, `wl-copy`, `xclip`, `xsel`, `pbcopy` or
+    /// `osc52`.
+    #[cfg(feature = "clipboard")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clipboard: Option<Backend>,
+    /// Selection `--yank` copies to: `clipboard` or `primary`.
+    #[cfg(feature = "clipboard")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clipboard_selection: Option<Selection>,
+    /// Longest number of seconds the native clipboard is served for on X11 and Wayland.
+    #[cfg(feature = "clipboard")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clipboard_lifetime: Option<u64>,
+    /// The clipboard keys are kept as they are when dym is built without the clipboard feature, so
+    /// configuration files still load and can be shared with builds that have it.
+    #[cfg(not(feature = "clipboard"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clipboard: Option<toml::Value>,
+    #[cfg(not(feature = "clipboard"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clipboard_selection: Option<toml::Value>,
+    #[cfg(not(feature = "clipboard"))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clipboard_lifetime: Option<toml::Value>,
+}
+
+impl Config {
+    /// Return the path of the configuration file. This is the value of `DYM_CONFIG` if set,
+    /// otherwise `didyoumean/config.toml` inside the platform configuration directory.
+    pub fn path() -> Option<PathBuf> {
+        env::var_os(CONFIG_ENV)
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| dirs::config_dir().map(|dir| dir.join("didyoumean").join("config.toml")))
+    }
+
+    /// Read the configuration file and apply the environment variables overriding it. A missing
+    /// file results in the default configuration.
+    pub fn load() -> Result<Config, Error> {
+        let mut config = Config::from_table(Config::read_table()?)?;
+
+        if let Some(source_url) = env::var(SOURCE_URL_ENV).ok().filter(|url| !url.is_empty()) {
+            config.source_url = Some(source_url);
+        }
+        if let Ok(mirrors) = env::var(MIRRORS_ENV) {
+            config.mirrors = Some(mirrors.split_whitespace().map(str::to_owned).collect());
+        }
+
+        Ok(config)
+    }
+
+    /// Return the configured language, or `DEFAULT_LANG`.
+    pub fn lang(&self) -> &str {
+        self.lang.as_deref().unwrap_or(DEFAULT_LANG)
+    }
+
+    /// Return the configured number of suggestions, or `DEFAULT_NUMBER`.
+    pub fn number(&self) -> usize {
+        self.number.unwrap_or(DEFAULT_NUMBER)
+    }
+
+    /// Return the configured download timeout, or `DEFAULT_TIMEOUT` seconds.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.unwrap_or(DEFAULT_TIMEOUT))
+    }
+
+    /// Return the configured number of download retries, or `DEFAULT_RETRIES`.
+    pub fn retries(&self) -> u32 {
+        self.retries.unwrap_or(DEFAULT_RETRIES)
+    }
+
+    /// Return the configured path of the daemon's socket, or `DEFAULT_SOCKET` in the user data
+    /// directory so a daemon only serves clients using the same word lists.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dirs` - The directories holding the word lists.
+    pub fn socket(&self, data_dirs: &DataDirs) -> Option<PathBuf> {
+        self.socket
+            .clone()
+            .or_else(|| data_dirs.user().map(|dir| dir.join(DEFAULT_SOCKET)))
+    }
+
+    /// Return the configured command typing words, or the default for the running display server.
+    pub fn type_command(&self) -> CommandLine {
+        match &self.type_command {
+            Some(command) => command.clone(),
+            None if env::var_os("WAYLAND_DISPLAY").is_some_and(|display| !display.is_empty()) => {
+                CommandLine::Line(DEFAULT_WAYLAND_TYPE_COMMAND.to_owned())
+            }
+            None => CommandLine::Line(DEFAULT_TYPE_COMMAND.to_owned()),
+        }
+    }
+
+    /// Return the configured clipboard selection and lifetime, or their defaults.
+    #[cfg(feature = "clipboard")]
+    pub fn clipboard_options(&self) -> clipboard::Options {
+        let defaults = clipboard::Options::default();
+        clipboard::Options {
+            selection: self.clipboard_selection.unwrap_or(defaults.selection),
+            lifetime: self
+                .clipboard_lifetime
+                .map_or(defaults.lifetime, Duration::from_secs),
+        }
+    }
+
+    /// Set `key` to `value` in the configuration file. `value` is parsed as a TOML value, falling
+    /// back to a plain string.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The configuration key to set.
+    /// * `value` - The new value of the key.
+    pub fn set(key: &str, value: &str) -> Result<(), Error> {
+        let value = format!("value = {}", value)
+            .parse::<toml::Table>()
+            .ok()
+            .and_then(|mut table| table.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(value.to_owned()));
+
+        let mut table = Config::read_table()?;
+        table.insert(key.to_owned(), value);
+        Config::write_table(table)
+    }
+
+    /// Remove `key` from the configuration file.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The configuration key to remove.
+    pub fn unset(key: &str) -> Result<(), Error> {
+        let mut table = Config::read_table()?;
+        if table.remove(key).is_none() {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("{} is not set in the configuration file", key),
+            ));
+        }
+        Config::write_table(table)
+    }
+
+    /// Read the configuration file as a TOML table. A missing file results in an empty table.
+    fn read_table() -> Result<toml::Table, Error> {
+        let path = match Config::path() {
+            Some(path) if path.is_file() => path,
+            _ => return Ok(toml::Table::new()),
+        };
+
+        read_to_string(&path)?
+            .parse::<toml::Table>()
+            .map_err(|error| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid configuration file {}: {}", path.display(), error),
+                )
+            })
+    }
+
+    /// Validate `table` and write it to the configuration file.
+    fn write_table(table: toml::Table) -> Result<(), Error> {
+        Config::from_table(table.clone())?;
+
+        let path = Config::path().ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "No configuration directory could be determined. Set {}",
+                    CONFIG_ENV
+                ),
+            )
+        })?;
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        write(path, table.to_string())
+    }
+
+    /// Convert a TOML table to a `Config`, rejecting unknown keys, values of the wrong type and
+    /// values out of range.
+    fn from_table(table: toml::Table) -> Result<Config, Error> {
+        let config: Config = table.try_into().map_err(|error: toml::de::Error| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid configuration: {}", error.message()),
+            )
+        })?;
+
+        // A zero timeout would make every download fail at once.
+        if config.timeout == Some(0) {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                "Invalid configuration: timeout must be at least 1 second",
+            ));
+        }
+
+        Ok(config)
+    }
+}
@@ -0,0 +1,229 @@
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::remove_file,
+    io::{BufRead, BufReader, Error, ErrorKind, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::Path,
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::{
+    cache::Dictionaries,
+    config::{Config, MAX_NUMBER},
+    workers,
+};
+use didyoumean::data::DataDirs;
+
+/// How long a client waits for the daemon before searching in-process instead.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A request sent to the daemon, as a single line of JSON.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum Request {
+    /// Suggest corrections for `word` in `lang`, or the daemon's default language.
+    Suggest {
+        word: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        lang: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        number: Option<usize>,
+    },
+    /// Check that the daemon is running.
+    Ping,
+}
+
+/// A suggestion in a response.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Suggestion {
+    pub word: String,
+    pub distance: usize,
+}
+
+/// The daemon's answer to a request, as a single line of JSON. Only the fields relevant to the
+/// request are set.
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct Response {
+    /// The suggestions for a `suggest` request, closest first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestions: Option<Vec<Suggestion>>,
+    /// The version of dym the daemon runs, in answer to `ping`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Why the request failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// The state shared by the connections of the daemon.
+struct Daemon {
+    dictionaries: Dictionaries,
+}
+
+/// Serve requests on the socket at `path` until the process is killed, loading the word lists
+/// of `langs` up front. Connections are answered by a fixed number of threads.
+///
+/// # Arguments
+///
+/// * `path` - The path of the Unix domain socket.
+/// * `langs` - The locale codes of the word lists to load right away.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+pub fn serve(
+    path: &Path,
+    langs: &[String],
+    config: Config,
+    data_dirs: DataDirs,
+) -> Result<i32, Error> {
+    // A socket left behind by a daemon that was killed is replaced, but a running daemon isn't.
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(Error::new(
+                ErrorKind::AddrInUse,
+                format!("A daemon is already listening on {}", path.display()),
+            ));
+        }
+        remove_file(path)?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let daemon = Arc::new(Daemon {
+        dictionaries: Dictionaries::new(config, data_dirs),
+    });
+    for lang in langs {
+        daemon.dictionaries.get(lang)?;
+    }
+
+    let listener = UnixListener::bind(path)?;
+    println!(
+        "{}",
+        format!("Listening on {}", path.display()).green().bold()
+    );
+
+    workers::serve(listener.incoming(), move |stream| daemon.handle(stream))?;
+
+    Ok(0)
+}
+
+impl Daemon {
+    /// Answer the requests of a connection, one per line, until the client disconnects.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The connection to the client.
+    fn handle(&self, stream: UnixStream) {
+        let Ok(reader) = stream.try_clone() else {
+            return;
+        };
+        let mut writer = stream;
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else {
+                return;
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let response = match serde_json::from_str(&line) {
+                Ok(request) => self.answer(request),
+                Err(error) => Response {
+                    error: Some(format!("Invalid request: {}", error)),
+                    ..Response::default()
+                },
+            };
+            let Ok(mut response) = serde_json::to_string(&response) else {
+                return;
+            };
+            response.push('\n');
+            if writer.write_all(response.as_bytes()).is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Return the response to `request`.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - A request from a client.
+    fn answer(&self, request: Request) -> Response {
+        match request {
+            Request::Ping => Response {
+                version: Some(env!("CARGO_PKG_VERSION").to_owned()),
+                ..Response::default()
+            },
+            Request::Suggest { word, lang, number } => {
+                let config = self.dictionaries.config();
+                let lang = lang.as_deref().unwrap_or(config.lang());
+                let number = number.unwrap_or(config.number());
+                if number > MAX_NUMBER {
+                    return Response {
+                        error: Some(format!("number can't be larger than {}", MAX_NUMBER)),
+                        ..Response::default()
+                    };
+                }
+                match self.dictionaries.get(lang) {
+                    Ok(dictionary) => Response {
+                        suggestions: Some(
+                            dictionary
+                                .suggest(&word, number)
+                                .into_iter()
+                                .map(|suggestion| Suggestion {
+                                    word: suggestion.word.to_owned(),
+                                    distance: suggestion.distance,
+                                })
+                                .collect(),
+                        ),
+                        ..Response::default()
+                    },
+                    Err(error) => Response {
+                        error: Some(error.to_string()),
+                        ..Response::default()
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// Ask the daemon listening on `path` for suggestions. Return `None` if no daemon is running or
+/// it couldn't answer, so the caller can search in-process instead.
+///
+/// # Arguments
+///
+/// * `path` - The path of the daemon's socket.
+/// * `word` - The word to suggest corrections for.
+/// * `lang` - The locale code of the word list.
+/// * `number` - The number of suggestions.
+pub fn suggest(path: &Path, word: &str, lang: &str, number: usize) -> Option<Vec<Suggestion>> {
+    let request = Request::Suggest {
+        word: word.to_owned(),
+        lang: Some(lang.to_owned()),
+        number: Some(number),
+    };
+    request_daemon(path, &request).ok()?.suggestions
+}
+
+/// Send `request` to the daemon listening on `path` and return its response.
+///
+/// # Arguments
+///
+/// * `path` - The path of the daemon's socket.
+/// * `request` - The request to send.
+fn request_daemon(path: &Path, request: &Request) -> Result<Response, Error> {
+    let mut stream = UnixStream::connect(path)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut line = serde_json::to_string(request).map_err(Error::other)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())?;
+
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response)?;
+    serde_json::from_str(&response).map_err(|error| Error::new(ErrorKind::InvalidData, error))
+}
@@ -0,0 +1,246 @@
+use std::{
+    collections::HashMap,
+    env,
+    fs::{create_dir_all, read_dir},
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+/// Environment variable used to override the user data directory.
+pub const DATA_DIR_ENV: &str = "DYM_DATA_DIR";
+
+/// Environment variable used to override the list of system-wide data directories.
+pub const SYSTEM_DATA_DIRS_ENV: &str = "DYM_SYSTEM_DATA_DIRS";
+
+/// Read-only directories where distribution packages can preinstall word lists.
+#[cfg(unix)]
+pub const SYSTEM_DATA_DIRS: &[&str] = &["/usr/local/share/didyoumean", "/usr/share/didyoumean"];
+#[cfg(not(unix))]
+pub const SYSTEM_DATA_DIRS: &[&str] = &[];
+
+/// Environment variable used to override the list of directories holding Hunspell dictionaries.
+pub const HUNSPELL_DIRS_ENV: &str = "DYM_HUNSPELL_DIRS";
+
+/// Directories where distributions install Hunspell dictionaries.
+#[cfg(unix)]
+pub const HUNSPELL_DIRS: &[&str] = &[
+    "/usr/local/share/hunspell",
+    "/usr/share/hunspell",
+    "/usr/share/myspell",
+    "/usr/share/myspell/dicts",
+];
+#[cfg(not(unix))]
+pub const HUNSPELL_DIRS: &[&str] = &[];
+
+/// The set of directories word lists are read from and downloaded to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DataDirs {
+    user: Option<PathBuf>,
+    system: Vec<PathBuf>,
+    hunspell: Vec<PathBuf>,
+    /// Names of the Hunspell dictionaries chosen for languages, such as `pt_BR` for `pt`.
+    hunspell_names: HashMap<String, String>,
+}
+
+impl DataDirs {
+    /// Resolve the data directories.
+    ///
+    /// The user directory is `data_dir` if given, otherwise the value of `DYM_DATA_DIR`, otherwise
+    /// `configured`, otherwise `didyoumean` inside the platform data directory. If none of these
+    /// exist, only the system directories are used. The system directories are read from
+    /// `DYM_SYSTEM_DATA_DIRS` if set, otherwise `SYSTEM_DATA_DIRS` is used, and the Hunspell
+    /// directories likewise from `DYM_HUNSPELL_DIRS` or `HUNSPELL_DIRS`.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dir` - An explicitly requested user data directory.
+    /// * `configured` - The user data directory set in a configuration file.
+    pub fn new(data_dir: Option<PathBuf>, configured: Option<PathBuf>) -> DataDirs {
+        let user = data_dir
+            .or_else(|| {
+                env::var_os(DATA_DIR_ENV)
+                    .filter(|dir| !dir.is_empty())
+                    .map(PathBuf::from)
+            })
+            .or(configured)
+            .or_else(|| dirs::data_dir().map(|dir| dir.join("didyoumean")));
+
+        DataDirs {
+            user,
+            system: dirs_from_env(SYSTEM_DATA_DIRS_ENV, SYSTEM_DATA_DIRS),
+            hunspell: dirs_from_env(HUNSPELL_DIRS_ENV, HUNSPELL_DIRS),
+            hunspell_names: HashMap::new(),
+        }
+    }
+
+    /// Create a `DataDirs` from explicit directories without consulting the environment.
+    ///
+    /// # Arguments
+    ///
+    /// * `user` - The writable user data directory, if any.
+    /// * `system` - Read-only directories, searched in order before `user`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use didyoumean::data::DataDirs;
+    /// # use std::path::{Path, PathBuf};
+    /// let dirs = DataDirs::with_dirs(Some(PathBuf::from("/tmp/dym")), vec![]);
+    /// assert_eq!(dirs.user(), Some(Path::new("/tmp/dym")));
+    /// ```
+    pub fn with_dirs(user: Option<PathBuf>, system: Vec<PathBuf>) -> DataDirs {
+        DataDirs {
+            user,
+            system,
+            hunspell: vec![],
+            hunspell_names: HashMap::new(),
+        }
+    }
+
+    /// Search `hunspell` for Hunspell dictionaries, in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `hunspell` - Directories holding `.dic` and `.aff` files.
+    pub fn with_hunspell_dirs(mut self, hunspell: Vec<PathBuf>) -> DataDirs {
+        self.hunspell = hunspell;
+        self
+    }
+
+    /// Use the Hunspell dictionaries named in `names` for their languages, such as `pt_BR` for
+    /// `pt`, instead of looking for one.
+    ///
+    /// # Arguments
+    ///
+    /// * `names` - Names of dictionaries without the `.dic` extension, by locale code.
+    pub fn with_hunspell_names(mut self, names: HashMap<String, String>) -> DataDirs {
+        self.hunspell_names = names;
+        self
+    }
+
+    /// Return the writable user data directory, if one could be determined.
+    pub fn user(&self) -> Option<&Path> {
+        self.user.as_deref()
+    }
+
+    /// Return the read-only system data directories.
+    pub fn system(&self) -> &[PathBuf] {
+        &self.system
+    }
+
+    /// Return the path of the word list for `lang`, searching the system directories before the
+    /// user directory. Return `None` if the word list is not installed anywhere.
+    ///
+    /// # Arguments
+    ///
+    /// * `lang` - The locale code of the word list.
+    pub fn find(&self, lang: &str) -> Option<PathBuf> {
+        self.system
+            .iter()
+            .chain(self.user.iter())
+            .map(|dir| dir.join(lang))
+            .find(|path| path.is_file())
+    }
+
+    /// Return the path of the Hunspell dictionary file for `lang`, which has an affix file next to
+    /// it. The dictionary chosen with `with_hunspell_names` is used if there is one. Otherwise a
+    /// dictionary named after the language alone, such as `he.dic`, is used, or the regional
+    /// variant such as `he_IL.dic` if it is the only one. Languages with several variants need
+    /// one to be chosen.
+    ///
+    /// # Arguments
+    ///
+    /// * `lang` - The locale code of the language.
+    pub fn find_hunspell(&self, lang: &str) -> Option<PathBuf> {
+        if let Some(name) = self.hunspell_names.get(lang) {
+            return self.hunspell_file(&format!("{}.dic", name));
+        }
+
+        self.hunspell_file(&format!("{}.dic", lang)).or_else(|| {
+            let mut variants = self.hunspell_variants(lang);
+            if variants.len() == 1 {
+                self.hunspell_file(&format!("{}.dic", variants.remove(0)))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Return the names of the regional Hunspell dictionaries of `lang`, such as `pt_BR` and
+    /// `pt_PT` for `pt`, in alphabetical order.
+    ///
+    /// # Arguments
+    ///
+    /// * `lang` - The locale code of the language.
+    pub fn hunspell_variants(&self, lang: &str) -> Vec<String> {
+        let prefix = format!("{}_", lang);
+        let mut variants: Vec<String> = self
+            .hunspell
+            .iter()
+            .filter_map(|dir| read_dir(dir).ok())
+            .flatten()
+            .filter_map(|entry| {
+                let path = entry.ok()?.path();
+                let name = path.file_name()?.to_str()?.strip_suffix(".dic")?;
+                (name.starts_with(&prefix) && path.with_extension("aff").is_file())
+                    .then(|| name.to_owned())
+            })
+            .collect();
+        variants.sort();
+        variants.dedup();
+        variants
+    }
+
+    /// Return the path of the first dictionary file named `file_name` in the Hunspell directories
+    /// that has an affix file next to it.
+    ///
+    /// # Arguments
+    ///
+    /// * `file_name` - The name of the `.dic` file.
+    fn hunspell_file(&self, file_name: &str) -> Option<PathBuf> {
+        self.hunspell
+            .iter()
+            .map(|dir| dir.join(file_name))
+            .find(|path| path.is_file() && path.with_extension("aff").is_file())
+    }
+
+    /// Return the path of the personal word list for `lang` inside the user data directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `lang` - The locale code of the personal word list.
+    pub fn personal(&self, lang: &str) -> Option<PathBuf> {
+        self.user().map(|dir| dir.join("personal").join(lang))
+    }
+
+    /// Return the user data directory, creating it if it doesn't exist.
+    pub fn ensure_user(&self) -> Result<&Path, Error> {
+        let dir = self.user().ok_or_else(|| {
+            Error::new(
+                ErrorKind::NotFound,
+                format!(
+                    "No data directory could be determined. Set {} or pass --data-dir",
+                    DATA_DIR_ENV
+                ),
+            )
+        })?;
+
+        create_dir_all(dir)?;
+        Ok(dir)
+    }
+}
+
+/// Return the directories listed in the environment variable `var`, or `default` if it isn't set.
+///
+/// # Arguments
+///
+/// * `var` - The name of the environment variable, a list of paths.
+/// * `default` - The directories used when the variable isn't set.
+fn dirs_from_env(var: &str, default: &[&str]) -> Vec<PathBuf> {
+    match env::var_os(var) {
+        Some(dirs) => env::split_paths(&dirs)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .collect(),
+        None => default.iter().map(PathBuf::from).collect(),
+    }
+}
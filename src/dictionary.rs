@@ -0,0 +1,201 @@
+use std::{
+    collections::HashSet,
+    fs::{create_dir_all, write},
+    io::Error,
+    path::Path,
+};
+
+use crate::{compression::read_word_list, edit_distance, insert_and_shift};
+
+/// A word suggested for a search term along with its edit distance to the search term.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Suggestion<'a> {
+    pub word: &'a str,
+    pub distance: usize,
+}
+
+/// A list of known words, kept in the order they were added.
+#[derive(Debug, Default, Clone)]
+pub struct Dictionary {
+    words: Vec<String>,
+    index: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Create an empty dictionary.
+    pub fn new() -> Dictionary {
+        Dictionary::default()
+    }
+
+    /// Create a dictionary from a newline separated word list. Blank lines are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `word_list` - The contents of a word list file.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use didyoumean::dictionary::Dictionary;
+    /// let dictionary = Dictionary::parse("hello\r\nworld\n\n");
+    /// assert_eq!(dictionary.len(), 2);
+    /// assert!(dictionary.contains("world"));
+    /// ```
+    pub fn parse(word_list: &str) -> Dictionary {
+        let mut dictionary = Dictionary::new();
+        dictionary.extend_from_list(word_list);
+        dictionary
+    }
+
+    /// Read a dictionary from the word list file at `path`, which may be gzip or Zstandard
+    /// compressed.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of a newline separated word list.
+    pub fn load(path: &Path) -> Result<Dictionary, Error> {
+        Ok(Dictionary::parse(&read_word_list(path)?))
+    }
+
+    /// Add the words of a newline separated word list to the dictionary.
+    ///
+    /// # Arguments
+    ///
+    /// * `word_list` - The contents of a word list file.
+    pub fn extend_from_list(&mut self, word_list: &str) {
+        self.extend(word_list.lines().map(str::trim));
+    }
+
+    /// Add `words` to the dictionary, skipping empty strings and words that are already known.
+    ///
+    /// # Arguments
+    ///
+    /// * `words` - The words to add.
+    pub fn extend<'a, I: IntoIterator<Item = &'a str>>(&mut self, words: I) {
+        for word in words {
+            if !word.is_empty() && self.index.insert(word.to_owned()) {
+                self.words.push(word.to_owned());
+            }
+        }
+    }
+
+    /// Remove `word` from the dictionary. Return `true` if it was present.
+    ///
+    /// # Arguments
+    ///
+    /// * `word` - The word to remove.
+    pub fn remove(&mut self, word: &str) -> bool {
+        if self.index.remove(word) {
+            self.words.retain(|known| known != word);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Write the dictionary to `path` as a newline separated word list, creating its parent
+    /// directory if it doesn't exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path of the word list file to write.
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent)?;
+        }
+
+        let mut word_list = self.words.join("\n");
+        word_list.push('\n');
+        write(path, word_list)
+    }
+
+    /// Return the number of words in the dictionary.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// Return `true` if the dictionary has no words.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+
+    /// Iterate over the words in the order they were added.
+    pub fn words(&self) -> impl Iterator<Item = &str> {
+        self.words.iter().map(String::as_str)
+    }
+
+    /// Return `true` if `word` is in the dictionary, either as is or in lowercase.
+    ///
+    /// # Arguments
+    ///
+    /// * `word` - The word to look up.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use didyoumean::dictionary::Dictionary;
+    /// let dictionary = Dictionary::parse("hello\nParis");
+    /// assert!(dictionary.contains("Hello"));
+    /// assert!(dictionary.contains("Paris"));
+    /// assert!(!dictionary.contains("paris"));
+    /// ```
+    pub fn contains(&self, word: &str) -> bool {
+        self.index.contains(word) || self.index.contains(&word.to_lowercase())
+    }
+
+    /// Return the `n` words closest to `search_term` ordered by edit distance. Words with equal
+    /// distances keep their order in the dictionary.
+    ///
+    /// # Arguments
+    ///
+    /// * `search_term` - The word to find suggestions for.
+    /// * `n` - The maximum number of suggestions to return.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use didyoumean::dictionary::Dictionary;
+    /// let dictionary = Dictionary::parse("world\nhelp\nhello");
+    /// let suggestions = dictionary.suggest("helo", 2);
+    ///
+    /// assert_eq!(suggestions[0].word, "help");
+    /// assert_eq!(suggestions[1].word, "hello");
+    /// assert_eq!(suggestions[1].distance, 1);
+    /// ```
+    pub fn suggest(&self, search_term: &str, n: usize) -> Vec<Suggestion<'_>> {
+        // There can't be more suggestions than words, however many are asked for.
+        let n = n.min(self.len());
+        if n == 0 {
+            return vec![];
+        }
+
+        // Create mutable vecs for storing the top n words.
+        let mut top_n_words = vec![""; n];
+        let mut top_n_dists = vec![usize::MAX; n];
+
+        // Loop over the words in the dictionary, run the algorithm, and
+        // add to the list if appropriate
+        let search_chars = search_term.chars().collect::<Vec<_>>();
+        for word in self.words() {
+            // Get edit distance.
+            let dist = edit_distance(&search_chars, word);
+
+            // Add to the list if appropriate.
+            if dist < top_n_dists[n - 1] {
+                for i in 0..n {
+                    if dist < top_n_dists[i] {
+                        insert_and_shift(&mut top_n_dists, i, dist);
+                        insert_and_shift(&mut top_n_words, i, word);
+                        break;
+                    }
+                }
+            }
+        }
+
+        top_n_words
+            .into_iter()
+            .zip(top_n_dists)
+            .map(|(word, distance)| Suggestion { word, distance })
+            .collect()
+    }
+}
@@ -0,0 +1,780 @@
+use colored::*;
+use futures_util::{stream, StreamExt};
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use reqwest::{
+    header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, IF_RANGE, LAST_MODIFIED, RANGE},
+    Client, NoProxy, Proxy, Response, StatusCode, Url,
+};
+use sha2::{Digest, Sha256};
+use std::{
+    cmp::min,
+    fs::{read, read_dir, read_to_string, remove_file, rename, write, File},
+    io::{Error, ErrorKind, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+use tokio::time::{sleep, timeout};
+
+use crate::config::Config;
+use crate::store::{self, Metadata};
+use didyoumean::{
+    compression::{decompress, Compression},
+    data::DataDirs,
+    langs::{LOCALES, SUPPORTED_LANGS},
+};
+
+/// Location of the word list repository.
+pub const BASE_URL: &str = "https://raw.githubusercontent.com/hisbaan/wordlists/main";
+
+/// Name of the optional checksum manifest in the word list repository, in `sha256sum` format.
+const MANIFEST: &str = "SHA256SUMS";
+
+/// The compressed variants of a word list a source may provide, in order of preference, followed
+/// by plain text.
+const VARIANTS: [Compression; 3] = [Compression::Zstd, Compression::Gzip, Compression::None];
+
+/// The maximum number of word lists downloaded at the same time.
+const MAX_CONCURRENT_DOWNLOADS: usize = 4;
+
+/// How long to wait before the first retry of a failed download. The delay doubles with every
+/// further retry, up to `MAX_RETRY_DELAY`.
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// The longest delay between two retries of a failed download.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// The result of downloading a word list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// A new version of the word list was downloaded.
+    Downloaded,
+    /// The source reported that the installed word list is up to date.
+    Unchanged,
+}
+
+/// A word list to download.
+#[derive(Debug, Clone)]
+pub struct Download {
+    /// Locale code of the word list.
+    pub lang: String,
+    /// Path to write the word list to.
+    pub path: PathBuf,
+    /// Metadata of the installed word list. If set, the source is asked to only send the word
+    /// list if it changed since it was downloaded.
+    pub previous: Option<Metadata>,
+}
+
+/// Downloads word lists from the configured repository and its mirrors.
+#[derive(Debug, Clone)]
+pub struct Downloader {
+    sources: Vec<String>,
+    timeout: Duration,
+    retries: u32,
+    proxy: Option<String>,
+    compression: Compression,
+}
+
+/// The state shared by concurrent downloads.
+struct Session {
+    client: Client,
+    progress: MultiProgress,
+    timeout: Duration,
+}
+
+impl Downloader {
+    /// Create a downloader for the sources in `config`: the `source_url` key followed by the
+    /// `mirrors` key. Without a `source_url`, https://github.com/hisbaan/wordlists is used. The
+    /// `timeout`, `retries` and `proxy` keys control how downloads are made, and the `compression`
+    /// key how they are stored.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration, with command line arguments and environment variables
+    ///   already applied.
+    pub fn new(config: &Config) -> Downloader {
+        let primary = config.source_url.as_deref().unwrap_or(BASE_URL);
+        let sources = std::iter::once(primary)
+            .chain(config.mirrors.iter().flatten().map(String::as_str))
+            .map(|source| source.trim_end_matches('/').to_owned())
+            .collect();
+
+        Downloader {
+            sources,
+            timeout: config.timeout(),
+            retries: config.retries(),
+            proxy: config.proxy.clone(),
+            compression: config.compression.unwrap_or_default(),
+        }
+    }
+
+    /// Fetch the word list specified by `lang` if it isn't installed yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `lang` - A locale code string to define the word list file to fetch.
+    /// * `data_dirs` - The directories to search for an installed copy and to download into.
+    pub fn fetch_word_list(&self, lang: &str, data_dirs: &DataDirs) -> Result<(), Error> {
+        self.fetch_word_lists(&[lang.to_owned()], data_dirs)
+    }
+
+    /// Concurrently fetch the word lists specified by `langs` that aren't installed yet. Return the
+    /// first error once every download has finished.
+    ///
+    /// # Arguments
+    ///
+    /// * `langs` - Locale code strings to define the word list files to fetch.
+    /// * `data_dirs` - The directories to search for installed copies and to download into.
+    pub fn fetch_word_lists(&self, langs: &[String], data_dirs: &DataDirs) -> Result<(), Error> {
+        let mut missing: Vec<&String> = langs
+            .iter()
+            .filter(|lang| data_dirs.find(lang).is_none())
+            .collect();
+        missing.sort();
+        missing.dedup();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+
+        // Fetch the files from the server into the user data directory, creating it if it
+        // doesn't exist.
+        let data = data_dirs.ensure_user()?;
+        let downloads: Vec<Download> = missing
+            .into_iter()
+            .map(|lang| Download {
+                lang: lang.to_owned(),
+                path: data.join(lang),
+                previous: None,
+            })
+            .collect();
+
+        match self
+            .download_word_lists(&downloads)
+            .into_iter()
+            .find_map(Result::err)
+        {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    /// Download the word list specified by `lang` to `file_path`, trying each source in turn.
+    ///
+    /// # Arguments
+    ///
+    /// * `lang` - A locale code string to define the word list file to fetch.
+    /// * `file_path` - The path to write the word list to.
+    /// * `previous` - The metadata of the installed word list. If given, the source is asked to
+    ///   only send the word list if it changed since it was downloaded.
+    pub fn download_word_list(
+        &self,
+        lang: &str,
+        file_path: &Path,
+        previous: Option<&Metadata>,
+    ) -> Result<Outcome, Error> {
+        let download = Download {
+            lang: lang.to_owned(),
+            path: file_path.to_owned(),
+            previous: previous.cloned(),
+        };
+
+        self.download_word_lists(&[download]).pop().unwrap()
+    }
+
+    /// Download several word lists concurrently, at most `MAX_CONCURRENT_DOWNLOADS` at a time,
+    /// showing one progress bar per word list. Return the result of each download in the order
+    /// they were given.
+    ///
+    /// Each word list is written to a temporary file next to its path and only moved into place
+    /// once its length, checksum (if the source publishes one) and encoding have been verified, so
+    /// an interrupted download never replaces a working word list.
+    ///
+    /// # Arguments
+    ///
+    /// * `downloads` - The word lists to download.
+    #[tokio::main]
+    pub async fn download_word_lists(&self, downloads: &[Download]) -> Vec<Result<Outcome, Error>> {
+        let session = match self.client() {
+            Ok(client) => Session {
+                client,
+                progress: MultiProgress::new(),
+                timeout: self.timeout,
+            },
+            Err(error) => {
+                return downloads
+                    .iter()
+                    .map(|_| Err(Error::new(error.kind(), error.to_string())))
+                    .collect()
+            }
+        };
+
+        stream::iter(downloads)
+            .map(|download| self.download(&session, download))
+            .buffered(MAX_CONCURRENT_DOWNLOADS)
+            .collect()
+            .await
+    }
+
+    /// Build the HTTP client used for downloads. Proxies are taken from the `HTTPS_PROXY`,
+    /// `HTTP_PROXY` and `NO_PROXY` environment variables unless the `proxy` key is set.
+    fn client(&self) -> Result<Client, Error> {
+        let mut builder = Client::builder().connect_timeout(self.timeout);
+        if let Some(proxy) = &self.proxy {
+            let proxy = Proxy::all(proxy)
+                .map_err(|error| {
+                    Error::new(
+                        ErrorKind::InvalidInput,
+                        format!("Invalid proxy {}: {}", proxy, error),
+                    )
+                })?
+                .no_proxy(NoProxy::from_env());
+            builder = builder.proxy(proxy);
+        }
+
+        builder.build().map_err(Error::other)
+    }
+
+    /// Download a single word list, trying each source in turn. Downloads failing because of a
+    /// network or server error are retried with exponential backoff before moving on to the next
+    /// source.
+    ///
+    /// # Arguments
+    ///
+    /// * `session` - The state shared by all downloads.
+    /// * `download` - The word list to download.
+    async fn download(&self, session: &Session, download: &Download) -> Result<Outcome, Error> {
+        let lang = download.lang.as_str();
+        let previous = download.previous.as_ref();
+        let name = LOCALES.get(lang).unwrap().to_string().blue();
+
+        // Print above the progress bars so they aren't drawn over.
+        let print = |message: String| session.progress.suspend(|| println!("{}", message));
+
+        if previous.is_some() {
+            print(format!("Checking {} word list for updates...", name));
+        } else {
+            print(format!("Downloading {} word list...", name));
+        }
+
+        let partial = store::partial_path(&download.path);
+        let mut last_error = None;
+
+        for (i, source) in self.sources.iter().enumerate() {
+            if i > 0 {
+                print(format!("Trying mirror {} for {}...", source, name));
+            }
+
+            // Validator of the partially downloaded word list, used to resume it when retrying.
+            let mut resume = None;
+            let mut attempt = 0;
+
+            // Download into a temporary file, cleaning it up if anything goes wrong.
+            let result = loop {
+                match download_from(session, source, lang, &partial, previous, &mut resume).await {
+                    Err(error) if attempt < self.retries && is_transient(&error) => {
+                        let delay = RETRY_DELAY
+                            .saturating_mul(2u32.saturating_pow(attempt))
+                            .min(MAX_RETRY_DELAY);
+                        attempt += 1;
+                        print(
+                            format!(
+                                "Failed to download {} from {}: {}. Retrying in {} ({}/{})...",
+                                lang,
+                                source,
+                                error,
+                                humantime::format_duration(delay),
+                                attempt,
+                                self.retries
+                            )
+                            .yellow()
+                            .to_string(),
+                        );
+                        sleep(delay).await;
+                    }
+                    result => break result,
+                }
+            };
+
+            match result {
+                Ok(Some(mut metadata)) => {
+                    // Compress the word list if it should be stored compressed.
+                    if self.compression != Compression::None {
+                        let compressed = self.compression.compress(&read(&partial)?)?;
+                        write(&partial, &compressed)?;
+                        metadata.size = compressed.len() as u64;
+                    }
+
+                    // Move the verified word list into place.
+                    rename(&partial, &download.path)?;
+                    store::write_metadata(&download.path, &metadata)?;
+                    return Ok(Outcome::Downloaded);
+                }
+                Ok(None) => return Ok(Outcome::Unchanged),
+                Err(error) => {
+                    remove_file(&partial).ok();
+                    print(
+                        format!("Failed to download from {}: {}", source, error)
+                            .red()
+                            .to_string(),
+                    );
+                    last_error = Some(error);
+                }
+            }
+        }
+
+        let error = last_error.unwrap();
+        Err(Error::new(
+            error.kind(),
+            format!("Failed to download {} word list: {}", lang, error),
+        ))
+    }
+
+    /// Concurrently update the word list files in the user data directory, only downloading the
+    /// ones that changed, and print a summary. Word lists in the read-only system directories are
+    /// left untouched. Return 1 if any update failed.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dirs` - The directories holding the word lists.
+    pub fn update_langs(&self, data_dirs: &DataDirs) -> Result<i32, Error> {
+        // Get files in data directory, creating it if it doesn't exist.
+        let data = data_dirs.ensure_user()?;
+        let mut langs = vec![];
+        for file in read_dir(data)? {
+            let file_name = file?.file_name();
+            let string: &str = file_name.to_str().unwrap_or_default();
+
+            // Only update supported languages.
+            if SUPPORTED_LANGS.contains_key(string) {
+                langs.push(string.to_owned());
+            }
+        }
+        langs.sort();
+
+        let downloads: Vec<Download> = langs
+            .into_iter()
+            .map(|lang| {
+                let path = data.join(&lang);
+
+                // Only trust the stored validators if the word list still matches them.
+                let previous = store::verify(&path)
+                    .ok()
+                    .and_then(|_| store::read_metadata(&path));
+
+                Download {
+                    lang,
+                    path,
+                    previous,
+                }
+            })
+            .collect();
+
+        let mut updated = vec![];
+        let mut unchanged = vec![];
+        let mut failed = vec![];
+
+        // The old word list is kept if the download fails.
+        let results = self.download_word_lists(&downloads);
+        for (download, result) in downloads.iter().zip(results) {
+            let name = *LOCALES.get(&download.lang).unwrap();
+            match result {
+                Ok(Outcome::Downloaded) => updated.push(name),
+                Ok(Outcome::Unchanged) => unchanged.push(name),
+                Err(_) => failed.push(name),
+            }
+        }
+
+        // Print summary.
+        if updated.is_empty() && unchanged.is_empty() && failed.is_empty() {
+            println!("No downloaded word lists to update");
+        }
+        if !updated.is_empty() {
+            println!("{} {}", "Updated:".green(), updated.join(", "));
+        }
+        if !unchanged.is_empty() {
+            println!("{} {}", "Unchanged:".blue(), unchanged.join(", "));
+        }
+        if !failed.is_empty() {
+            println!("{} {}", "Failed:".red(), failed.join(", "));
+        }
+
+        Ok(if failed.is_empty() { 0 } else { 1 })
+    }
+}
+
+/// Download the word list for `lang` from `source` to `path` and verify it. Return the metadata of
+/// the downloaded word list, or `None` if it didn't change since `previous` was downloaded.
+///
+/// # Arguments
+///
+/// * `session` - The state shared by all downloads.
+/// * `source` - The base URL to download from. `file://` URLs are read from the file system.
+/// * `lang` - The locale code of the word list.
+/// * `path` - The path to write the word list to.
+/// * `previous` - The metadata of the installed word list, if any.
+/// * `resume` - The `If-Range` validator of the word list partially downloaded to `path` by a
+///   previous attempt, if any. It is updated with the validator of the response.
+async fn download_from(
+    session: &Session,
+    source: &str,
+    lang: &str,
+    path: &Path,
+    previous: Option<&Metadata>,
+    resume: &mut Option<String>,
+) -> Result<Option<Metadata>, Error> {
+    // Validators are only meaningful for the source that issued them.
+    let previous = previous.filter(|previous| previous.source.as_deref() == Some(source));
+
+    if let Some(dir) = local_path(source) {
+        let expected_sha256 = read_to_string(dir.join(MANIFEST))
+            .ok()
+            .and_then(|manifest| parse_manifest(&manifest, lang));
+
+        // Prefer a compressed copy of the word list if there is one.
+        let file = VARIANTS
+            .iter()
+            .map(|compression| dir.join(format!("{}{}", lang, compression.extension())))
+            .find(|file| file.is_file())
+            .unwrap_or_else(|| dir.join(lang));
+        let contents = decompress(read(file)?)?;
+        let digest = Sha256::digest(&contents);
+
+        // Local files are compared by checksum instead of HTTP validators.
+        if previous.is_some_and(|previous| previous.sha256 == format!("{:x}", digest)) {
+            return Ok(None);
+        }
+
+        write(path, &contents)?;
+        let size = contents.len() as u64;
+        let metadata = verify_download(path, None, size, size, digest, expected_sha256)?;
+
+        return Ok(Some(Metadata {
+            source: Some(source.to_owned()),
+            ..metadata
+        }));
+    }
+
+    let expected_sha256 = fetch_checksum(session, source, lang).await;
+
+    // Resume the partial download of a previous attempt if there is one.
+    let partial_size = match resume {
+        Some(_) => path.metadata().map_or(0, |metadata| metadata.len()),
+        None => 0,
+    };
+
+    // Prefer compressed variants of the word list, falling back to plain text. Only plain text
+    // downloads can be resumed, as decompression can't continue halfway through a stream.
+    let variants = match resume {
+        Some(_) if partial_size > 0 => &[Compression::None][..],
+        _ => &VARIANTS[..],
+    };
+
+    let mut variants = variants.iter();
+    let (compression, response) = loop {
+        let compression = *variants.next().unwrap();
+
+        // Setup reqwest. Resumed downloads ask for the rest of the word list if it didn't change
+        // in the meantime, other downloads only ask for it if it changed since it was last
+        // downloaded.
+        let url = format!("{}/{}{}", source, lang, compression.extension());
+        let mut request = session.client.get(&url);
+        if let Some(validator) = resume.as_ref().filter(|_| partial_size > 0) {
+            request = request
+                .header(RANGE, format!("bytes={}-", partial_size))
+                .header(IF_RANGE, validator);
+        } else {
+            if let Some(etag) = previous.and_then(|previous| previous.etag.as_ref()) {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) =
+                previous.and_then(|previous| previous.last_modified.as_ref())
+            {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let response = timeout(session.timeout, request.send())
+            .await
+            .map_err(|_| Error::new(ErrorKind::TimedOut, "timed out waiting for a response"))?
+            .map_err(Error::other)?;
+
+        // Sources don't have to provide compressed variants.
+        if response.status() == StatusCode::NOT_FOUND && compression != Compression::None {
+            continue;
+        }
+
+        break (
+            compression,
+            response.error_for_status().map_err(Error::other)?,
+        );
+    };
+
+    if response.status() == StatusCode::NOT_MODIFIED {
+        return Ok(None);
+    }
+
+    // Remember the validators for the next update.
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_owned)
+    };
+    let etag = header(ETAG);
+    let last_modified = header(LAST_MODIFIED);
+
+    // Only strong entity tags can be used to resume a download.
+    *resume = etag
+        .clone()
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| last_modified.clone())
+        .filter(|_| compression == Compression::None);
+
+    // Append to the partial download if the server sent the rest of it, otherwise start over.
+    let mut downloaded: u64 = 0;
+    let sink = if response.status() == StatusCode::PARTIAL_CONTENT {
+        let mut hasher = Sha256::new();
+        hasher.update(read(path)?);
+        downloaded = partial_size;
+        Sink {
+            file: File::options().append(true).open(path)?,
+            hasher,
+            size: partial_size,
+        }
+    } else {
+        Sink {
+            file: File::create(path)?,
+            hasher: Sha256::new(),
+            size: 0,
+        }
+    };
+
+    // Decompress compressed variants while they are downloaded.
+    let mut decoder = compression.decoder(sink)?;
+    let total_size = response.content_length().map(|size| size + downloaded);
+    let mut stream = response.bytes_stream();
+
+    // Setup indicatif, labelling the bar with the language. A spinner is shown instead of a bar
+    // if the server didn't announce the size of the word list.
+    let pb = match total_size {
+        Some(total_size) => {
+            let pb = ProgressBar::new(total_size);
+            pb.set_style(
+                ProgressStyle::default_bar()
+                    .template("{prefix:>12} [{elapsed_precise}] [{wide_bar:.blue/cyan}] {bytes}/{total_bytes} ({eta})")
+                    .unwrap()
+                    .progress_chars("#>-"),
+            );
+            pb
+        }
+        None => {
+            let pb = ProgressBar::new_spinner();
+            pb.set_style(
+                ProgressStyle::default_spinner()
+                    .template("{prefix:>12} [{elapsed_precise}] {spinner:.blue} {bytes} ({bytes_per_sec})")
+                    .unwrap(),
+            );
+            pb.enable_steady_tick(Duration::from_millis(100));
+            pb
+        }
+    };
+    let pb = session.progress.add(pb);
+    pb.set_prefix(*LOCALES.get(lang).unwrap());
+    pb.set_position(downloaded);
+
+    // Read from stream into file, giving up if no data arrives in time.
+    while let Some(item) = timeout(session.timeout, stream.next())
+        .await
+        .map_err(|_| Error::new(ErrorKind::TimedOut, "timed out waiting for data"))?
+    {
+        let chunk = item.map_err(Error::other)?;
+        decoder
+            .write_all(&chunk)
+            .map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+        downloaded += chunk.len() as u64;
+        pb.set_position(min(downloaded, total_size.unwrap_or(downloaded)));
+    }
+    let sink = decoder
+        .finish()
+        .map_err(|error| Error::new(ErrorKind::InvalidData, error))?;
+    sink.file.sync_all()?;
+
+    // Print completed bar.
+    pb.finish();
+
+    let metadata = verify_download(
+        path,
+        total_size,
+        downloaded,
+        sink.size,
+        sink.hasher.finalize(),
+        expected_sha256,
+    )?;
+
+    Ok(Some(Metadata {
+        source: Some(source.to_owned()),
+        etag,
+        last_modified,
+        ..metadata
+    }))
+}
+
+/// Writes a downloaded word list to a file while computing its size and checksum.
+struct Sink {
+    file: File,
+    hasher: Sha256,
+    size: u64,
+}
+
+impl Write for Sink {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let written = self.file.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> Result<(), Error> {
+        self.file.flush()
+    }
+}
+
+/// Return whether downloading again may fix `error`: timeouts, connection problems, interrupted
+/// transfers, server errors and rate limiting.
+///
+/// # Arguments
+///
+/// * `error` - The error a download failed with.
+fn is_transient(error: &Error) -> bool {
+    let reqwest_error = error
+        .get_ref()
+        .and_then(|error| error.downcast_ref::<reqwest::Error>());
+
+    match reqwest_error {
+        Some(error) => match error.status() {
+            Some(status) => status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS,
+            None => {
+                error.is_timeout()
+                    || error.is_connect()
+                    || error.is_request()
+                    || error.is_body()
+                    || error.is_decode()
+            }
+        },
+        None => matches!(
+            error.kind(),
+            ErrorKind::TimedOut
+                | ErrorKind::UnexpectedEof
+                | ErrorKind::ConnectionReset
+                | ErrorKind::ConnectionAborted
+                | ErrorKind::Interrupted
+        ),
+    }
+}
+
+/// Check a downloaded word list. Return its metadata if it is complete, matches the expected
+/// checksum and is valid UTF-8.
+///
+/// # Arguments
+///
+/// * `path` - The path of the downloaded word list.
+/// * `total_size` - The size announced by the server, if any.
+/// * `downloaded` - The number of bytes received.
+/// * `size` - The size of the word list after decompressing it.
+/// * `digest` - The SHA-256 digest of the word list after decompressing it.
+/// * `expected_sha256` - The hex encoded SHA-256 checksum the word list should have, if known.
+fn verify_download(
+    path: &Path,
+    total_size: Option<u64>,
+    downloaded: u64,
+    size: u64,
+    digest: impl std::fmt::LowerHex,
+    expected_sha256: Option<String>,
+) -> Result<Metadata, Error> {
+    if let Some(expected) = total_size.filter(|&expected| expected != downloaded) {
+        // A short download was most likely interrupted and can be resumed.
+        let kind = if downloaded < expected {
+            ErrorKind::UnexpectedEof
+        } else {
+            ErrorKind::InvalidData
+        };
+        return Err(Error::new(
+            kind,
+            format!(
+                "expected {} bytes but received {} bytes",
+                expected, downloaded
+            ),
+        ));
+    }
+
+    let sha256 = format!("{:x}", digest);
+    if let Some(expected) = expected_sha256.filter(|expected| *expected != sha256) {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!("expected checksum {} but found {}", expected, sha256),
+        ));
+    }
+
+    if std::str::from_utf8(&read(path)?).is_err() {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "the word list is not valid UTF-8",
+        ));
+    }
+
+    Ok(Metadata {
+        size,
+        sha256,
+        ..Metadata::default()
+    })
+}
+
+/// Return the directory a `file://` source points to, or `None` for other sources.
+///
+/// # Arguments
+///
+/// * `source` - The base URL of a source.
+fn local_path(source: &str) -> Option<PathBuf> {
+    Url::parse(source)
+        .ok()
+        .filter(|url| url.scheme() == "file")?
+        .to_file_path()
+        .ok()
+}
+
+/// Return the SHA-256 checksum of the word list for `lang` listed in the manifest of `source`.
+/// Return `None` if the source has no manifest or it doesn't list `lang`.
+///
+/// # Arguments
+///
+/// * `session` - The state shared by all downloads.
+/// * `source` - The base URL of the source.
+/// * `lang` - The locale code of the word list.
+async fn fetch_checksum(session: &Session, source: &str, lang: &str) -> Option<String> {
+    let url = format!("{}/{}", source, MANIFEST);
+    let manifest = timeout(session.timeout, session.client.get(&url).send())
+        .await
+        .ok()?
+        .and_then(Response::error_for_status)
+        .ok()?
+        .text()
+        .await
+        .ok()?;
+
+    parse_manifest(&manifest, lang)
+}
+
+/// Find the checksum of `name` in a manifest in the format produced by `sha256sum`.
+///
+/// # Arguments
+///
+/// * `manifest` - The contents of the manifest.
+/// * `name` - The file name to look up.
+fn parse_manifest(manifest: &str, name: &str) -> Option<String> {
+    manifest.lines().find_map(|line| {
+        let (sha256, file) = line.split_once(char::is_whitespace)?;
+        // Binary mode entries prefix the file name with an asterisk.
+        let file = file.trim_start().trim_start_matches('*');
+        (file == name).then(|| sha256.to_lowercase())
+    })
+}
@@ -0,0 +1,31 @@
+use didyoumean::compression::decompress;
+
+// Define `EMBEDDED`, the locale codes and Zstandard compressed word lists selected with the
+// `embed-*` features, generated by `build.rs`.
+include!(concat!(env!("OUT_DIR"), "/embedded.rs"));
+
+/// Return the word list for `lang` if it is embedded into the binary.
+///
+/// # Arguments
+///
+/// * `lang` - A locale code string.
+pub fn word_list(lang: &str) -> Option<String> {
+    let (_, compressed) = EMBEDDED.iter().find(|(embedded, _)| *embedded == lang)?;
+
+    // Embedded word lists were checked when they were compressed at build time.
+    let word_list = decompress(compressed.to_vec()).expect("Embedded word list is corrupt");
+    Some(String::from_utf8(word_list).expect("Embedded word list is not valid UTF-8"))
+}
+
+/// Return the compressed size in bytes of the word list for `lang` if it is embedded into the
+/// binary.
+///
+/// # Arguments
+///
+/// * `lang` - A locale code string.
+pub fn size(lang: &str) -> Option<usize> {
+    EMBEDDED
+        .iter()
+        .find(|(embedded, _)| *embedded == lang)
+        .map(|(_, compressed)| compressed.len())
+}
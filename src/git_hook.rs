@@ -0,0 +1,252 @@
+use colored::*;
+use std::{
+    fs::{read_to_string, write},
+    io::{self, Error, ErrorKind},
+    path::{Path, PathBuf},
+    process,
+};
+
+use crate::{config::Config, load_dictionary};
+use didyoumean::{
+    check::check,
+    data::DataDirs,
+    report::{self, Diagnostic, Format},
+};
+
+/// The word list at the top of a repository holding words that are only known in that repository.
+pub const REPO_WORD_LIST: &str = ".dym-words";
+
+/// A line in the hooks installed by dym, so they can be told apart from other hooks.
+const HOOK_MARKER: &str = "# Installed by dym";
+
+/// The line after which `git commit --verbose` appends the diff, following the comment character.
+const SCISSORS: &str = " ------------------------ >8 ------------------------";
+
+/// Check the spelling of the commit message in `file`, printing each misspelled word with its
+/// suggestions as `dym check` does. Words in the word list of the repository are known too.
+/// Return 1 if a word is misspelled in strict mode, so git aborts the commit.
+///
+/// # Arguments
+///
+/// * `file` - The commit message file, as given to the `commit-msg` hook.
+/// * `strict` - Whether misspelled words abort the commit.
+/// * `number` - The number of suggestions printed for each misspelled word.
+/// * `lang` - The locale code of the word list.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+pub fn commit_msg(
+    file: &Path,
+    strict: bool,
+    number: usize,
+    lang: &str,
+    config: &Config,
+    data_dirs: &DataDirs,
+) -> Result<i32, Error> {
+    let mut dictionary = load_dictionary(lang, config, data_dirs)?;
+    if let Some(word_list) = repo_word_list().filter(|path| path.is_file()) {
+        dictionary.extend_from_list(&read_to_string(word_list)?);
+    }
+
+    let message = commit_message(&read_to_string(file)?, comment_char());
+    let name = file.display().to_string();
+    let diagnostics: Vec<Diagnostic> = check(&message, &dictionary, number)
+        .into_iter()
+        .map(|misspelling| Diagnostic::new(&name, misspelling))
+        .collect();
+    report::write(&mut io::stdout(), Format::Quickfix, &diagnostics)?;
+
+    if diagnostics.is_empty() {
+        return Ok(0);
+    }
+    let hint = format!(
+        "Add words to {} or your personal word list with `dym dict add` if they are correct",
+        REPO_WORD_LIST
+    );
+    if strict {
+        println!(
+            "{}",
+            "Commit aborted because of misspelled words".red().bold()
+        );
+        println!("{}", hint.dimmed());
+        Ok(1)
+    } else {
+        println!("{}", hint.dimmed());
+        Ok(0)
+    }
+}
+
+/// Install the `commit-msg` hook of the current repository, checking commit messages with dym.
+/// A hook that wasn't installed by dym is only replaced with `force`.
+///
+/// # Arguments
+///
+/// * `strict` - Whether the hook aborts commits with misspelled words.
+/// * `force` - Whether to replace an existing hook.
+pub fn install(strict: bool, force: bool) -> Result<i32, Error> {
+    let hooks = PathBuf::from(git(&["rev-parse", "--git-path", "hooks"])?);
+    let path = hooks.join("commit-msg");
+
+    if !force
+        && path.is_file()
+        && !read_to_string(&path).is_ok_and(|hook| hook.contains(HOOK_MARKER))
+    {
+        return Err(Error::new(
+            ErrorKind::AlreadyExists,
+            format!(
+                "{} already exists, use --force to replace it",
+                path.display()
+            ),
+        ));
+    }
+
+    std::fs::create_dir_all(&hooks)?;
+    let strict = if strict { " --strict" } else { "" };
+    write(
+        &path,
+        format!(
+            "#!/bin/sh\n{}: check the spelling of commit messages.\nexec dym git-hook commit-msg{} \"$1\"\n",
+            HOOK_MARKER, strict
+        ),
+    )?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+
+    println!(
+        "{}",
+        format!("Installed the commit-msg hook in {}", path.display()).green()
+    );
+    Ok(0)
+}
+
+/// Return `true` if `line` is a trailer such as `Signed-off-by: Name <email>`.
+///
+/// # Arguments
+///
+/// * `line` - A line of the commit message.
+fn is_trailer(line: &str) -> bool {
+    line.split_once(':').is_some_and(|(token, _)| {
+        !token.is_empty()
+            && !token.starts_with('-')
+            && token.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+    })
+}
+
+/// Return the prose of a commit message, blanking the lines that aren't written by the author so
+/// line numbers are kept: comment lines, the diff added by `git commit --verbose` or by the
+/// scissors line, and the trailers of the last paragraph.
+///
+/// # Arguments
+///
+/// * `text` - The commit message file, as given to the `commit-msg` hook.
+/// * `comment_char` - The character starting comment lines, `#` unless `core.commentChar` is set.
+fn commit_message(text: &str, comment_char: char) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+
+    // Everything after the scissors line or the start of a diff is left out.
+    let end = lines
+        .iter()
+        .position(|line| {
+            line.strip_prefix(comment_char) == Some(SCISSORS) || line.starts_with("diff --git ")
+        })
+        .unwrap_or(lines.len());
+    let mut kept: Vec<&str> = lines[..end]
+        .iter()
+        .map(|&line| {
+            if line.starts_with(comment_char) {
+                ""
+            } else {
+                line
+            }
+        })
+        .collect();
+
+    // Trailers are the last paragraph if it isn't the subject and is made of `Token: value` lines,
+    // which may continue on indented lines.
+    if let Some(last) = kept.iter().rposition(|line| !line.trim().is_empty()) {
+        let start = kept[..last]
+            .iter()
+            .rposition(|line| line.trim().is_empty())
+            .map_or(0, |i| i + 1);
+        let paragraph = &kept[start..=last];
+        if start > 0
+            && is_trailer(paragraph[0])
+            && paragraph
+                .iter()
+                .all(|line| is_trailer(line) || line.starts_with(char::is_whitespace))
+        {
+            kept[start..=last].fill("");
+        }
+    }
+
+    kept.join("\n")
+}
+
+/// Return the path of the word list of the current repository, or `None` outside a repository.
+fn repo_word_list() -> Option<PathBuf> {
+    let top = git(&["rev-parse", "--show-toplevel"]).ok()?;
+    Some(Path::new(&top).join(REPO_WORD_LIST))
+}
+
+/// Return the character starting comment lines in commit messages, set by `core.commentChar`.
+fn comment_char() -> char {
+    let comment_char = git(&["config", "core.commentChar"]).unwrap_or_default();
+    let mut chars = comment_char.chars();
+    match (chars.next(), chars.next()) {
+        // `auto` picks a character that doesn't start a line of the message, which is only known
+        // to git, so it falls back to the default like an unset key.
+        (Some(c), None) => c,
+        _ => '#',
+    }
+}
+
+/// Run git with `args` in the current directory and return its output without the trailing
+/// newline.
+///
+/// # Arguments
+///
+/// * `args` - The arguments to git.
+fn git(args: &[&str]) -> Result<String, Error> {
+    let output = process::Command::new("git")
+        .args(args)
+        .stderr(process::Stdio::null())
+        .output()?;
+    if !output.status.success() {
+        return Err(Error::new(
+            ErrorKind::NotFound,
+            format!("`git {}` failed, is this a git repository?", args.join(" ")),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .trim_end()
+        .to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commit_message_test() {
+        let text = "Fix teh parser\n\nSigned-off-by: Jane Doe <jane@example.com>\n# Plese enter\n";
+        assert_eq!(commit_message(text, '#'), "Fix teh parser\n\n\n");
+
+        // A trailer-like subject is kept, and so is a last paragraph that isn't all trailers.
+        assert_eq!(commit_message("Fix: teh parser\n", '#'), "Fix: teh parser");
+        assert_eq!(
+            commit_message("Fix\n\nNote: teh\nsee bleow\n", '#'),
+            "Fix\n\nNote: teh\nsee bleow"
+        );
+
+        // Nothing after the scissors line is checked, using the configured comment character.
+        assert_eq!(
+            commit_message(
+                "Fix\n; ------------------------ >8 ------------------------\ndiff\n",
+                ';'
+            ),
+            "Fix"
+        );
+    }
+}
@@ -0,0 +1,403 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs::read,
+    io::{Error, ErrorKind},
+    path::Path,
+};
+
+use crate::dictionary::Dictionary;
+use encoding_rs::Encoding;
+
+/// How flags are written in the affix and dictionary files, set by the `FLAG` directive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FlagType {
+    /// Each character is a flag.
+    Char,
+    /// Each pair of characters is a flag.
+    Long,
+    /// Flags are decimal numbers separated by commas.
+    Num,
+}
+
+/// A character of an affix condition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Condition {
+    /// `.`, any character.
+    Any,
+    /// `[abc]`, or `[^abc]` when negated.
+    Set(Vec<char>, bool),
+    /// Any other character, matching itself.
+    Char(char),
+}
+
+impl Condition {
+    /// Return `true` if `c` satisfies the condition.
+    fn matches(&self, c: char) -> bool {
+        match self {
+            Condition::Any => true,
+            Condition::Set(chars, negated) => chars.contains(&c) != *negated,
+            Condition::Char(expected) => *expected == c,
+        }
+    }
+}
+
+/// A prefix or suffix rule.
+#[derive(Debug, Clone)]
+struct Affix {
+    /// Whether the affix combines with affixes of the other kind.
+    cross: bool,
+    /// The characters removed from the stem before adding the affix.
+    strip: String,
+    /// The characters added to the stem.
+    add: String,
+    /// Flags of the suffixes that can follow this suffix.
+    continuation: Vec<String>,
+    /// What the start of the stem (for prefixes) or its end (for suffixes) must look like.
+    condition: Vec<Condition>,
+}
+
+impl Affix {
+    /// Return `stem` with the affix added as a prefix, or `None` if the rule doesn't apply.
+    fn prefix(&self, stem: &[char]) -> Option<String> {
+        let rest: String = stem.iter().collect();
+        let rest = rest.strip_prefix(&self.strip)?;
+        if stem.len() < self.condition.len()
+            || !self
+                .condition
+                .iter()
+                .zip(stem)
+                .all(|(condition, &c)| condition.matches(c))
+        {
+            return None;
+        }
+        Some(format!("{}{}", self.add, rest))
+    }
+
+    /// Return `stem` with the affix added as a suffix, or `None` if the rule doesn't apply.
+    fn suffix(&self, stem: &[char]) -> Option<String> {
+        let rest: String = stem.iter().collect();
+        let rest = rest.strip_suffix(&self.strip)?;
+        if stem.len() < self.condition.len()
+            || !self
+                .condition
+                .iter()
+                .rev()
+                .zip(stem.iter().rev())
+                .all(|(condition, &c)| condition.matches(c))
+        {
+            return None;
+        }
+        Some(format!("{}{}", rest, self.add))
+    }
+}
+
+/// The rules of an affix file that matter for listing words.
+#[derive(Debug)]
+struct Affixes {
+    flag_type: FlagType,
+    /// Flag sets referred to by number in the dictionary file, set by `AF`.
+    aliases: Vec<Vec<String>>,
+    prefixes: HashMap<String, Vec<Affix>>,
+    suffixes: HashMap<String, Vec<Affix>>,
+    /// Flags of stems that are only words with an affix or inside compounds, or not words at all.
+    not_words: HashSet<String>,
+}
+
+impl Affixes {
+    /// Parse the affix file `aff`.
+    fn parse(aff: &str) -> Affixes {
+        let mut affixes = Affixes {
+            flag_type: FlagType::Char,
+            aliases: vec![],
+            prefixes: HashMap::new(),
+            suffixes: HashMap::new(),
+            not_words: HashSet::new(),
+        };
+        // Whether each affix flag combines with the other kind, from the header of its rules.
+        let mut cross = HashMap::new();
+        // The first `AF` line gives the number of aliases rather than an alias.
+        let mut alias_count_seen = false;
+
+        for line in aff.lines() {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match fields.as_slice() {
+                ["FLAG", flag_type, ..] => {
+                    affixes.flag_type = match *flag_type {
+                        "long" => FlagType::Long,
+                        "num" => FlagType::Num,
+                        _ => FlagType::Char,
+                    }
+                }
+                ["AF", flags, ..] => {
+                    if alias_count_seen {
+                        let flags = affixes.split_flags(flags);
+                        affixes.aliases.push(flags);
+                    }
+                    alias_count_seen = true;
+                }
+                ["NEEDAFFIX" | "ONLYINCOMPOUND" | "FORBIDDENWORD", flag, ..] => {
+                    affixes.not_words.extend(affixes.split_flags(flag));
+                }
+                [kind @ ("PFX" | "SFX"), flag, cross_product @ ("Y" | "N"), count]
+                    if count.parse::<usize>().is_ok() =>
+                {
+                    cross.insert((*kind, flag.to_string()), *cross_product == "Y");
+                }
+                [kind @ ("PFX" | "SFX"), flag, strip, add, rest @ ..] => {
+                    let (add, continuation) = match add.split_once('/') {
+                        Some((add, continuation)) => (add, affixes.flags(continuation)),
+                        None => (*add, vec![]),
+                    };
+                    let affix = Affix {
+                        cross: cross
+                            .get(&(*kind, flag.to_string()))
+                            .copied()
+                            .unwrap_or(false),
+                        strip: empty_if_zero(strip).to_owned(),
+                        add: empty_if_zero(add).to_owned(),
+                        continuation,
+                        condition: parse_condition(rest.first().copied().unwrap_or(".")),
+                    };
+                    let rules = if *kind == "PFX" {
+                        &mut affixes.prefixes
+                    } else {
+                        &mut affixes.suffixes
+                    };
+                    rules.entry(flag.to_string()).or_default().push(affix);
+                }
+                _ => {}
+            }
+        }
+
+        affixes
+    }
+
+    /// Split `flags` into flags, resolving a number into its alias if the file has any.
+    fn flags(&self, flags: &str) -> Vec<String> {
+        if !self.aliases.is_empty() {
+            if let Ok(index) = flags.parse::<usize>() {
+                return index
+                    .checked_sub(1)
+                    .and_then(|index| self.aliases.get(index))
+                    .cloned()
+                    .unwrap_or_default();
+            }
+        }
+        self.split_flags(flags)
+    }
+
+    /// Split `flags` into flags as written with the flag type of the file.
+    fn split_flags(&self, flags: &str) -> Vec<String> {
+        match self.flag_type {
+            FlagType::Char => flags.chars().map(String::from).collect(),
+            FlagType::Long => flags
+                .chars()
+                .collect::<Vec<_>>()
+                .chunks(2)
+                .map(|pair| pair.iter().collect())
+                .collect(),
+            FlagType::Num => flags.split(',').map(str::to_owned).collect(),
+        }
+    }
+
+    /// Add `stem` and every form made by its affixes to `words`.
+    ///
+    /// # Arguments
+    ///
+    /// * `stem` - A stem of the dictionary file.
+    /// * `flags` - The flags of the stem.
+    /// * `words` - The words found so far.
+    fn expand(&self, stem: &str, flags: &[String], words: &mut Vec<String>) {
+        if !flags.iter().any(|flag| self.not_words.contains(flag)) {
+            words.push(stem.to_owned());
+        }
+
+        let chars: Vec<char> = stem.chars().collect();
+        let prefixes: Vec<&Affix> = flags
+            .iter()
+            .filter_map(|flag| self.prefixes.get(flag))
+            .flatten()
+            .collect();
+
+        for prefix in &prefixes {
+            words.extend(prefix.prefix(&chars));
+        }
+        for suffix in flags
+            .iter()
+            .filter_map(|flag| self.suffixes.get(flag))
+            .flatten()
+        {
+            let Some(word) = suffix.suffix(&chars) else {
+                continue;
+            };
+
+            // Suffixes can be followed by another suffix, but only once.
+            let suffixed: Vec<char> = word.chars().collect();
+            for next in suffix
+                .continuation
+                .iter()
+                .filter_map(|flag| self.suffixes.get(flag))
+                .flatten()
+            {
+                words.extend(next.suffix(&suffixed));
+            }
+
+            // Prefixes are checked against the stem, as the suffix only changes its end.
+            if suffix.cross {
+                for prefix in prefixes.iter().filter(|prefix| prefix.cross) {
+                    if prefix.prefix(&chars).is_some() {
+                        words.extend(prefix.prefix(&suffixed));
+                    }
+                }
+            }
+            words.push(word);
+        }
+    }
+}
+
+/// Return `field`, or an empty string if it is `0`, which stands for nothing in affix rules.
+fn empty_if_zero(field: &str) -> &str {
+    if field == "0" {
+        ""
+    } else {
+        field
+    }
+}
+
+/// Parse an affix condition such as `[^aeiou]y`.
+fn parse_condition(condition: &str) -> Vec<Condition> {
+    let mut parsed = vec![];
+    let mut chars = condition.chars();
+    while let Some(c) = chars.next() {
+        parsed.push(match c {
+            '.' => Condition::Any,
+            '[' => {
+                let mut set: Vec<char> = chars.by_ref().take_while(|&c| c != ']').collect();
+                let negated = set.first() == Some(&'^');
+                if negated {
+                    set.remove(0);
+                }
+                Condition::Set(set, negated)
+            }
+            c => Condition::Char(c),
+        });
+    }
+    parsed
+}
+
+/// Split a line of a dictionary file into its stem and flags, leaving out morphological fields.
+/// Slashes in the stem are escaped with a backslash.
+fn split_entry(line: &str) -> (String, &str) {
+    let entry = line.split(['\t', ' ']).next().unwrap_or_default();
+    let mut stem = String::new();
+    let mut chars = entry.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => stem.extend(chars.next().map(|(_, c)| c)),
+            '/' => return (stem, &entry[i + 1..]),
+            c => stem.push(c),
+        }
+    }
+    (stem, "")
+}
+
+/// Return every word of a Hunspell dictionary: the stems of the dictionary file `dic` and the
+/// forms made by adding the prefixes and suffixes of the affix file `aff` allowed by their flags.
+/// Compounds aren't listed.
+///
+/// # Arguments
+///
+/// * `aff` - The contents of the affix file.
+/// * `dic` - The contents of the dictionary file.
+///
+/// # Examples
+///
+/// ```
+/// # use didyoumean::hunspell::parse;
+/// let aff = "SFX S Y 2\nSFX S y ies [^aeiou]y\nSFX S 0 s [aeiou]y\n\
+///            PFX U Y 1\nPFX U 0 un .\n";
+/// let dictionary = parse(aff, "2\nday/S\ntidy/SU\n");
+///
+/// assert!(dictionary.contains("days"));
+/// assert!(dictionary.contains("tidies"));
+/// assert!(dictionary.contains("untidies"));
+/// assert!(!dictionary.contains("tidys"));
+/// ```
+pub fn parse(aff: &str, dic: &str) -> Dictionary {
+    let affixes = Affixes::parse(aff);
+
+    let mut words = vec![];
+    for (i, line) in dic.lines().enumerate() {
+        // The first line is the number of stems, which is only a hint.
+        let count = i == 0 && line.trim().parse::<usize>().is_ok();
+        if count || line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (stem, flags) = split_entry(line);
+        affixes.expand(&stem, &affixes.flags(flags), &mut words);
+    }
+
+    let mut dictionary = Dictionary::new();
+    dictionary.extend(words.iter().map(String::as_str));
+    dictionary
+}
+
+/// Read the Hunspell dictionary file at `dic` along with the affix file next to it, and return
+/// every word it lists. The files are decoded with the encoding given by the `SET` directive of
+/// the affix file, ISO8859-1 if there is none.
+///
+/// # Arguments
+///
+/// * `dic` - The path of the `.dic` file.
+pub fn load(dic: &Path) -> Result<Dictionary, Error> {
+    let aff = read(dic.with_extension("aff"))?;
+
+    // The encoding is given in ASCII, before anything that needs decoding.
+    let name = aff
+        .split(|&byte| byte == b'\n')
+        .find_map(|line| line.strip_prefix(b"SET "))
+        .map(|name| String::from_utf8_lossy(name).trim().to_ascii_lowercase())
+        .unwrap_or_else(|| "iso8859-1".to_owned());
+    let encoding = match name.as_str() {
+        // The WHATWG encodings read ISO8859-1 as windows-1252, which maps some bytes to other
+        // characters.
+        "iso8859-1" | "iso-8859-1" => None,
+        _ => Some(encoding(&name).ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("{} uses the unknown {} encoding", dic.display(), name),
+            )
+        })?),
+    };
+    let decode = |bytes: &[u8]| -> Result<String, Error> {
+        let Some(encoding) = encoding else {
+            // Latin-1 bytes are the first 256 Unicode code points.
+            return Ok(bytes.iter().map(|&byte| char::from(byte)).collect());
+        };
+        let (text, malformed) = encoding.decode_without_bom_handling(bytes);
+        if malformed {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("{} is not valid {}", dic.display(), encoding.name()),
+            ));
+        }
+        Ok(text.into_owned())
+    };
+
+    Ok(parse(&decode(&aff)?, &decode(&read(dic)?)?))
+}
+
+/// Return the encoding named by the `SET` directive of an affix file. Hunspell names some
+/// encodings differently from the WHATWG labels, such as `microsoft-cp1251` for
+/// `windows-1251`.
+///
+/// # Arguments
+///
+/// * `name` - The lowercase name of the encoding.
+fn encoding(name: &str) -> Option<&'static Encoding> {
+    let label = match name.strip_prefix("microsoft-cp") {
+        Some(code_page) => format!("windows-{}", code_page),
+        None => name.replacen("iso8859-", "iso-8859-", 1),
+    };
+    Encoding::for_label(label.as_bytes())
+}
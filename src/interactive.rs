@@ -0,0 +1,460 @@
+use colored::*;
+use console::{truncate_str, Key, Term};
+use std::{
+    collections::HashMap,
+    io::{Error, ErrorKind},
+};
+
+use crate::{config::Config, copy_to_clipboard, embedded, hunspell_dictionary, load_dictionary};
+use didyoumean::{
+    data::DataDirs,
+    dictionary::Dictionary,
+    langs::{self, LOCALES, SUPPORTED_LANGS},
+};
+
+/// The character sent by Ctrl+U, which clears the query.
+const CTRL_U: char = '\u{15}';
+
+/// The character sent by Ctrl+W, which deletes the last word of the query.
+const CTRL_W: char = '\u{17}';
+
+/// The character sent by Ctrl+Y, which copies the selected word to the clipboard.
+const CTRL_Y: char = '\u{19}';
+
+/// The lines of the finder that aren't suggestions: the title, the query and the status line.
+const CHROME_LINES: usize = 3;
+
+/// The state of the interactive finder.
+struct Finder<'a> {
+    /// Loads the dictionary of a language.
+    load: &'a dyn Fn(&str) -> Result<Dictionary, Error>,
+    /// Locale codes of the languages that can be switched to without downloading anything.
+    langs: Vec<String>,
+    /// Index of the current language in `langs`.
+    lang: usize,
+    /// Dictionaries of the languages used so far, kept so switching back doesn't reload them.
+    dictionaries: HashMap<String, Dictionary>,
+    query: String,
+    number: usize,
+    /// Index of the selected suggestion.
+    selected: usize,
+    /// A message shown below the suggestions, such as an error.
+    status: String,
+    /// The number of lines drawn last, which are cleared before drawing again.
+    drawn: usize,
+}
+
+/// How the finder was closed.
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    /// A word was chosen to be printed.
+    Print(String),
+    /// A word was copied to the clipboard.
+    Yank(String),
+    /// The finder was closed without choosing a word.
+    Cancel,
+}
+
+/// What the finder does after handling a key.
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    /// Keep reading keys.
+    Continue,
+    /// Copy the word to the clipboard and close the finder if that works.
+    Yank(String),
+    /// Close the finder.
+    Close(Outcome),
+}
+
+/// Run the interactive finder on the terminal, updating the suggestions for the query as it is
+/// typed. The chosen word is printed to standard output, while the finder is drawn on standard
+/// error so the word can be captured. Return 1 if no word was chosen.
+///
+/// # Arguments
+///
+/// * `query` - The initial query.
+/// * `lang` - The locale code of the initial language.
+/// * `number` - The number of suggestions to show.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+pub fn run(
+    query: &str,
+    lang: &str,
+    number: usize,
+    config: &Config,
+    data_dirs: &DataDirs,
+) -> Result<i32, Error> {
+    let term = Term::stderr();
+    if !term.is_term() {
+        return Err(Error::new(
+            ErrorKind::Unsupported,
+            "The interactive finder needs a terminal",
+        ));
+    }
+
+    // Only offer languages that are available offline, so switching is instant.
+    let mut langs: Vec<String> = SUPPORTED_LANGS
+        .keys()
+        .filter(|code| data_dirs.find(code).is_some() || embedded::size(code).is_some())
+        .chain(
+            LOCALES
+                .keys()
+                .filter(|code| hunspell_dictionary(code, data_dirs).is_some()),
+        )
+        .map(|code| code.to_string())
+        .collect();
+    if !langs.iter().any(|code| code == lang) {
+        langs.push(lang.to_owned());
+    }
+    langs.sort();
+
+    let load = |lang: &str| load_dictionary(lang, config, data_dirs);
+    let mut finder = Finder {
+        load: &load,
+        lang: langs.iter().position(|code| code == lang).unwrap_or(0),
+        langs,
+        dictionaries: HashMap::new(),
+        query: query.to_owned(),
+        number,
+        selected: 0,
+        status: String::new(),
+        drawn: 0,
+    };
+    finder.load()?;
+
+    term.hide_cursor()?;
+    let outcome = finder.interact(&term, config);
+    term.clear_last_lines(finder.drawn).ok();
+    term.show_cursor()?;
+
+    match outcome? {
+        Outcome::Print(word) => {
+            println!("{}", word);
+            Ok(0)
+        }
+        Outcome::Yank(word) => {
+            eprintln!("{}", format!("\"{}\" copied to clipboard", word).green());
+            Ok(0)
+        }
+        Outcome::Cancel => {
+            eprintln!("{}", "No selection made".red());
+            Ok(1)
+        }
+    }
+}
+
+impl Finder<'_> {
+    /// Return the locale code of the current language.
+    fn lang(&self) -> &str {
+        &self.langs[self.lang]
+    }
+
+    /// Load the dictionary of the current language unless it was loaded before.
+    fn load(&mut self) -> Result<(), Error> {
+        let lang = self.lang().to_owned();
+        if !self.dictionaries.contains_key(&lang) {
+            let dictionary = (self.load)(&lang)?;
+            self.dictionaries.insert(lang, dictionary);
+        }
+        Ok(())
+    }
+
+    /// Return the words suggested for the query, with their edit distances.
+    fn suggestions(&self) -> Vec<(String, usize)> {
+        if self.query.is_empty() {
+            return vec![];
+        }
+
+        self.dictionaries[self.lang()]
+            .suggest(&self.query, self.number)
+            .into_iter()
+            .map(|suggestion| (suggestion.word.to_owned(), suggestion.distance))
+            .collect()
+    }
+
+    /// Return the suggestions for the query, keeping the selection within them.
+    fn refresh(&mut self) -> Vec<(String, usize)> {
+        let suggestions = self.suggestions();
+        self.selected = self.selected.min(suggestions.len().saturating_sub(1));
+        suggestions
+    }
+
+    /// Read keys and redraw the finder until a word is chosen or the finder is closed.
+    ///
+    /// # Arguments
+    ///
+    /// * `term` - The terminal to draw on and read keys from.
+    /// * `config` - The configuration file, which chooses the clipboard.
+    fn interact(&mut self, term: &Term, config: &Config) -> Result<Outcome, Error> {
+        loop {
+            let suggestions = self.refresh();
+            self.draw(term, &suggestions)?;
+
+            match self.handle_key(term.read_key_raw()?, &suggestions) {
+                Step::Continue => {}
+                Step::Yank(word) => match copy_to_clipboard(&word, config) {
+                    Ok(()) => return Ok(Outcome::Yank(word)),
+                    Err(error) => self.status = error.to_string(),
+                },
+                Step::Close(outcome) => return Ok(outcome),
+            }
+        }
+    }
+
+    /// Update the finder for a key that was pressed.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The key.
+    /// * `suggestions` - The suggestions shown when the key was pressed.
+    fn handle_key(&mut self, key: Key, suggestions: &[(String, usize)]) -> Step {
+        let selected = suggestions
+            .get(self.selected)
+            .map(|(word, _)| word.to_owned());
+        match key {
+            Key::Escape | Key::CtrlC => return Step::Close(Outcome::Cancel),
+            Key::Enter => {
+                if let Some(word) = selected {
+                    return Step::Close(Outcome::Print(word));
+                }
+            }
+            Key::Char(CTRL_Y) => match selected {
+                Some(word) => return Step::Yank(word),
+                None => self.status = "Nothing to copy".to_owned(),
+            },
+            Key::ArrowUp if !suggestions.is_empty() => {
+                self.selected = (self.selected + suggestions.len() - 1) % suggestions.len();
+            }
+            Key::ArrowDown if !suggestions.is_empty() => {
+                self.selected = (self.selected + 1) % suggestions.len();
+            }
+            Key::Tab => self.switch_lang(1),
+            Key::BackTab => self.switch_lang(self.langs.len() - 1),
+            Key::Backspace => {
+                self.query.pop();
+                self.edited();
+            }
+            Key::Char(CTRL_U) => {
+                self.query.clear();
+                self.edited();
+            }
+            Key::Char(CTRL_W) => {
+                let end = self.query.trim_end().rfind(' ').map_or(0, |i| i + 1);
+                self.query.truncate(end);
+                self.edited();
+            }
+            Key::Char(c) if !c.is_control() => {
+                self.query.push(c);
+                self.edited();
+            }
+            _ => {}
+        }
+        Step::Continue
+    }
+
+    /// Select the best suggestion again after the query changed.
+    fn edited(&mut self) {
+        self.selected = 0;
+        self.status.clear();
+    }
+
+    /// Move `offset` languages forward, wrapping around, and load the new language. The previous
+    /// language is kept if the new one can't be loaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `offset` - The number of languages to move forward by.
+    fn switch_lang(&mut self, offset: usize) {
+        let previous = self.lang;
+        self.lang = (self.lang + offset) % self.langs.len();
+        self.status.clear();
+        if let Err(error) = self.load() {
+            self.status = format!("{} can't be loaded: {}", self.lang(), error);
+            self.lang = previous;
+        }
+        self.selected = 0;
+    }
+
+    /// Replace the lines drawn last with the current state of the finder.
+    ///
+    /// # Arguments
+    ///
+    /// * `term` - The terminal to draw on.
+    /// * `suggestions` - The words suggested for the query, with their edit distances.
+    fn draw(&mut self, term: &Term, suggestions: &[(String, usize)]) -> Result<(), Error> {
+        let lang = self.lang();
+        let mut lines = vec![
+            format!(
+                "{} {} {}",
+                "Did you mean?".blue().bold(),
+                format!("[{}: {}]", lang, langs::name(lang).unwrap_or(lang)).purple(),
+                "[↑↓ to move, ↵ to print, ctrl-y to yank, tab to switch language, esc to cancel]"
+                    .dimmed()
+            ),
+            format!("{} {}", ">".yellow().bold(), self.query),
+        ];
+
+        // Leave room for the rest of the finder on small terminals.
+        let rows = (term.size().0 as usize).saturating_sub(CHROME_LINES).max(1);
+        let indent = self.number.to_string().len();
+        for (i, (word, distance)) in suggestions.iter().enumerate().take(rows) {
+            let number = format!("{:>indent$}.", i + 1).purple();
+            let distance = format!("({})", distance).dimmed();
+            if i == self.selected {
+                lines.push(format!(
+                    "{} {} {} {}",
+                    "❯".green(),
+                    number,
+                    word.bold(),
+                    distance
+                ));
+            } else {
+                lines.push(format!("  {} {} {}", number, word, distance));
+            }
+        }
+        lines.push(self.status.red().to_string());
+
+        // Lines wider than the terminal would wrap onto rows that aren't cleared on the next draw.
+        let width = term.size().1 as usize;
+        term.clear_last_lines(self.drawn)?;
+        for line in &lines {
+            term.write_line(&truncate_str(line, width, "…"))?;
+        }
+        self.drawn = lines.len();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Load a small dictionary for English and German, and fail for other languages.
+    fn load(lang: &str) -> Result<Dictionary, Error> {
+        match lang {
+            "en" => Ok(Dictionary::parse("hello\nhelp\nworld\nword\n")),
+            "de" => Ok(Dictionary::parse("hallo\nwelt\n")),
+            _ => Err(Error::new(ErrorKind::NotFound, "not installed")),
+        }
+    }
+
+    /// Create a finder showing English suggestions for `query`.
+    fn english(query: &str) -> Finder<'static> {
+        let mut finder = Finder {
+            load: &load,
+            langs: vec!["de".to_owned(), "en".to_owned(), "fr".to_owned()],
+            lang: 1,
+            dictionaries: HashMap::new(),
+            query: query.to_owned(),
+            number: 3,
+            selected: 0,
+            status: String::new(),
+            drawn: 0,
+        };
+        finder.load().unwrap();
+        finder
+    }
+
+    /// Press `keys` in order, returning the last step.
+    fn press(finder: &mut Finder, keys: &[Key]) -> Step {
+        let mut step = Step::Continue;
+        for key in keys {
+            let suggestions = finder.refresh();
+            step = finder.handle_key(key.clone(), &suggestions);
+        }
+        step
+    }
+
+    #[test]
+    fn edit_test() {
+        let mut finder = english("hel");
+        assert_eq!(finder.refresh()[0].0, "help");
+
+        // Typing ranks the suggestions again and selects the best one.
+        press(&mut finder, &[Key::ArrowDown, Key::Char('l')]);
+        assert_eq!(finder.query, "hell");
+        assert_eq!(finder.selected, 0);
+        assert_eq!(
+            press(&mut finder, &[Key::Enter]),
+            Step::Close(Outcome::Print("hello".to_owned()))
+        );
+
+        press(
+            &mut finder,
+            &[Key::Backspace, Key::Backspace, Key::Char('p')],
+        );
+        assert_eq!(
+            press(&mut finder, &[Key::Char(CTRL_Y)]),
+            Step::Yank("help".to_owned())
+        );
+    }
+
+    #[test]
+    fn delete_test() {
+        let mut finder = english("hello wrld ");
+        press(&mut finder, &[Key::Char(CTRL_W)]);
+        assert_eq!(finder.query, "hello ");
+        press(&mut finder, &[Key::Char(CTRL_W)]);
+        assert_eq!(finder.query, "");
+
+        let mut finder = english("hello wrld");
+        press(&mut finder, &[Key::Char(CTRL_U)]);
+        assert_eq!(finder.query, "");
+        assert!(finder.refresh().is_empty());
+
+        // There is nothing to choose without suggestions.
+        assert_eq!(press(&mut finder, &[Key::Enter]), Step::Continue);
+        assert_eq!(press(&mut finder, &[Key::Char(CTRL_Y)]), Step::Continue);
+        assert_eq!(finder.status, "Nothing to copy");
+        assert_eq!(
+            press(&mut finder, &[Key::Escape]),
+            Step::Close(Outcome::Cancel)
+        );
+    }
+
+    #[test]
+    fn selection_test() {
+        let mut finder = english("wrld");
+        let count = finder.refresh().len();
+        assert_eq!(count, 3);
+
+        // The selection wraps around the suggestions.
+        press(&mut finder, &[Key::ArrowUp]);
+        assert_eq!(finder.selected, count - 1);
+        press(&mut finder, &[Key::ArrowDown]);
+        assert_eq!(finder.selected, 0);
+
+        // Fewer suggestions pull the selection back onto the last one.
+        finder.selected = 2;
+        finder.number = 2;
+        assert_eq!(finder.refresh().len(), 2);
+        assert_eq!(finder.selected, 1);
+    }
+
+    #[test]
+    fn switch_lang_test() {
+        let mut finder = english("halo");
+
+        // A language that can't be loaded keeps the current one and says why.
+        press(&mut finder, &[Key::Tab]);
+        assert_eq!(finder.lang(), "en");
+        assert_eq!(finder.status, "fr can't be loaded: not installed");
+
+        press(&mut finder, &[Key::BackTab]);
+        assert_eq!(finder.lang(), "de");
+        assert!(finder.status.is_empty());
+        assert_eq!(finder.refresh()[0].0, "hallo");
+
+        // Going back from the first language wraps around to the last one, which fails again.
+        press(&mut finder, &[Key::BackTab]);
+        assert_eq!(finder.lang(), "de");
+        assert_eq!(finder.status, "fr can't be loaded: not installed");
+
+        // Going forward from the last language wraps around to the first one.
+        finder.langs.pop();
+        press(&mut finder, &[Key::Tab]);
+        assert_eq!(finder.lang(), "en");
+        press(&mut finder, &[Key::Tab]);
+        assert_eq!(finder.lang(), "de");
+    }
+}
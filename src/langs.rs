@@ -143,3 +143,25 @@ pub static SUPPORTED_LANGS: phf::Map<&'static str, &'static str> = phf_map! {
     "yi" => "Yiddish",
     "zu" => "Zulu",
 };
+
+/// Return the name of the language with the locale code `code`, whether or not there is a word
+/// list for it.
+///
+/// # Arguments
+///
+/// * `code` - The locale code of the language.
+///
+/// # Examples
+///
+/// ```
+/// # use didyoumean::langs::name;
+/// assert_eq!(name("en"), Some("English"));
+/// assert_eq!(name("he"), Some("Hebrew"));
+/// assert_eq!(name("xx"), None);
+/// ```
+pub fn name(code: &str) -> Option<&'static str> {
+    SUPPORTED_LANGS
+        .get(code)
+        .or_else(|| LOCALES.get(code))
+        .copied()
+}
@@ -1,61 +1,31 @@
-use cli_clipboard::{ClipboardContext, ClipboardProvider};
-use colored::*;
-use std::cmp::min;
+pub mod check;
+#[cfg(feature = "clipboard")]
+pub mod clipboard;
+pub mod compression;
+pub mod data;
+pub mod dictionary;
+pub mod hunspell;
+pub mod langs;
+pub mod report;
 
-#[cfg(unix)]
-use nix::unistd::{fork, ForkResult};
+use std::cmp::min;
 
-/// Copy `string` to the system clipboard
+/// Copy `string` to the system clipboard, choosing the clipboard automatically. Use
+/// `clipboard::yank` to choose the clipboard and handle errors.
 ///
 /// # Arguments
 ///
 /// * `string` - the string to be copied.
+#[cfg(feature = "clipboard")]
 pub fn yank(string: &str) {
-    let platform = std::env::consts::OS;
-    if vec![
-        "linux",
-        "freebsd",
-        "netbsd",
-        "dragonfly",
-        "netbsd",
-        "openbsd",
-        "solaris",
-    ]
-    .contains(&platform)
-    {
-        // The platform is linux/*bsd and is likely using X11 or Wayland.
-        // There is a fix needed for clipboard use in cases like these.
-        // The clipboard is cleared on X11/Wayland after the process that set it exist.
-        // To combat this, we will fork and keep a process around until the clipboard
-        // is cleared.
-        // Ideally, this wouldn't be an issue but it was a conscious design decision
-        // on X11/Wayland
-        #[cfg(unix)]
-        match unsafe { fork() } {
-            Ok(ForkResult::Child) => {
-                let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
-                ctx.set_contents(string.to_owned()).unwrap();
+    use colored::*;
 
-                // Keep the process running until the clipboard changes.
-                loop {
-                    let clipboard = ctx.get_contents().unwrap();
-                    std::thread::sleep(std::time::Duration::from_secs(1));
-                    if clipboard != string {
-                        std::process::exit(0);
-                    }
-                }
-            }
-            Err(_) => {
-                println!("{}", "Error: Clipboard fork failed".red());
-                std::process::exit(1);
-            }
-            _ => {}
-        }
-    } else {
-        // The platform is NOT running X11/Wayland and thus, we don't have to handle
-        // the clipboard clearing behaviour.
-        let mut ctx: ClipboardContext = ClipboardProvider::new().unwrap();
-        ctx.set_contents(string.to_owned()).unwrap();
+    if let Err(error) = clipboard::yank(
+        string,
+        clipboard::Backend::Auto,
+        &clipboard::Options::default(),
+    ) {
+        println!("{}", format!("Error: {}", error).red());
     }
 }
 
@@ -92,8 +62,8 @@ pub fn insert_and_shift<T: Copy>(list: &mut Vec<T>, index: usize, element: T) {
 /// # Arguments
 ///
 /// * `search_chars` - The first `Vec<char>` to compare, in most time search_term will not change, so
-/// we would like to share the same `Vec<char>` between multiple calls. you could use `search_string.chars().collect::<Vec<_>>()` to
-/// convert a string to a `Vec<char>`
+///   we would like to share the same `Vec<char>` between multiple calls. you could use `search_string.chars().collect::<Vec<_>>()` to
+///   convert a string to a `Vec<char>`
 /// * `known_term` - The second string to compare
 ///
 /// # Examples
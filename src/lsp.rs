@@ -0,0 +1,444 @@
+use serde_json::{json, Value};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Error, ErrorKind, Write},
+};
+
+use crate::{
+    cache::Dictionaries,
+    config::{Config, MAX_NUMBER},
+    load_personal,
+};
+use didyoumean::{check::check, data::DataDirs, report::Diagnostic};
+
+/// The command of the code action adding a word to the personal word list.
+const ADD_TO_DICTIONARY: &str = "didyoumean.addToDictionary";
+
+/// The source of the diagnostics published by the server.
+const SOURCE: &str = "dym";
+
+/// `TextDocumentSyncKind.Full`: documents are sent whole on every change.
+const SYNC_FULL: u8 = 1;
+
+/// `DiagnosticSeverity.Information`.
+const SEVERITY_INFORMATION: u8 = 3;
+
+/// JSON-RPC error code for requests that aren't implemented.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// JSON-RPC error code for requests with invalid parameters.
+const INVALID_PARAMS: i64 = -32602;
+
+/// JSON-RPC error code for messages that aren't valid JSON.
+const PARSE_ERROR: i64 = -32700;
+
+/// A misspelled word in an open document, with its position in UTF-16 code units as LSP expects.
+struct Misspelling {
+    word: String,
+    line: usize,
+    start: usize,
+    end: usize,
+    suggestions: Vec<String>,
+    /// The message of the diagnostic, as printed by `dym check`.
+    message: String,
+}
+
+impl Misspelling {
+    /// Return the LSP range covering the word.
+    fn range(&self) -> Value {
+        json!({
+            "start": {"line": self.line, "character": self.start},
+            "end": {"line": self.line, "character": self.end},
+        })
+    }
+}
+
+/// An open document and the misspelled words found in it.
+struct Document {
+    text: String,
+    misspellings: Vec<Misspelling>,
+}
+
+/// The state of the language server.
+struct Server {
+    dictionaries: Dictionaries,
+    lang: String,
+    number: usize,
+    /// Open documents, by URI.
+    documents: HashMap<String, Document>,
+    /// Whether the client asked the server to shut down.
+    shutdown: bool,
+}
+
+/// Run a language server on standard input and output, publishing diagnostics for the misspelled
+/// words of open documents and offering their suggestions as code actions. Only installed word
+/// lists are used, and missing or corrupt ones are reported to the client rather than downloaded,
+/// so nothing but protocol messages is written to standard output. Return the exit code requested
+/// by the protocol: 0 if the client shut the server down before it exits, 1 otherwise.
+///
+/// # Arguments
+///
+/// * `lang` - The locale code of the word list, unless the client sets `lang` in its
+///   initialization options.
+/// * `number` - The number of suggestions offered for each misspelled word.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+pub fn run(lang: &str, number: usize, config: Config, data_dirs: DataDirs) -> Result<i32, Error> {
+    let mut server = Server {
+        dictionaries: Dictionaries::new(config, data_dirs),
+        lang: lang.to_owned(),
+        number,
+        documents: HashMap::new(),
+        shutdown: false,
+    };
+
+    let mut input = io::stdin().lock();
+    while let Some(message) = read_message(&mut input)? {
+        // The framing is intact, so the server can carry on with the next message.
+        let message = match message {
+            Ok(message) => message,
+            Err(error) => {
+                send(&json!({
+                    "jsonrpc": "2.0",
+                    "id": null,
+                    "error": {"code": PARSE_ERROR, "message": error.to_string()},
+                }))?;
+                continue;
+            }
+        };
+        if message.get("method").and_then(Value::as_str) == Some("exit") {
+            return Ok(if server.shutdown { 0 } else { 1 });
+        }
+        server.handle(message)?;
+    }
+
+    Ok(if server.shutdown { 0 } else { 1 })
+}
+
+impl Server {
+    /// Handle a request or notification from the client.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - The JSON-RPC message.
+    fn handle(&mut self, message: Value) -> Result<(), Error> {
+        let method = message
+            .get("method")
+            .and_then(Value::as_str)
+            .unwrap_or_default();
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+
+        // Notifications have no id and get no response.
+        let Some(id) = message.get("id").cloned() else {
+            return match method {
+                "textDocument/didOpen" => {
+                    let document = &params["textDocument"];
+                    self.update(document["uri"].as_str(), document["text"].as_str())
+                }
+                "textDocument/didChange" => self.update(
+                    params["textDocument"]["uri"].as_str(),
+                    params["contentChanges"]
+                        .as_array()
+                        .and_then(|changes| changes.last())
+                        .and_then(|change| change["text"].as_str()),
+                ),
+                "textDocument/didClose" => {
+                    let Some(uri) = params["textDocument"]["uri"].as_str() else {
+                        return Ok(());
+                    };
+                    self.documents.remove(uri);
+                    publish(uri, &[])
+                }
+                _ => Ok(()),
+            };
+        };
+
+        let result = match method {
+            "initialize" => Ok(self.initialize(&params)),
+            "shutdown" => {
+                self.shutdown = true;
+                Ok(Value::Null)
+            }
+            "textDocument/codeAction" => Ok(self.code_actions(&params)),
+            "workspace/executeCommand" => self.execute_command(&params),
+            _ => Err((METHOD_NOT_FOUND, format!("{} is not supported", method))),
+        };
+
+        match result {
+            Ok(result) => send(&json!({"jsonrpc": "2.0", "id": id, "result": result})),
+            Err((code, message)) => send(&json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": {"code": code, "message": message},
+            })),
+        }
+    }
+
+    /// Apply the client's initialization options and return the capabilities of the server.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the `initialize` request.
+    fn initialize(&mut self, params: &Value) -> Value {
+        let options = &params["initializationOptions"];
+        if let Some(lang) = options["lang"].as_str() {
+            self.lang = lang.to_owned();
+        }
+        if let Some(number) = options["number"].as_u64().filter(|number| *number > 0) {
+            self.number = number.min(MAX_NUMBER as u64) as usize;
+        }
+
+        json!({
+            "capabilities": {
+                "textDocumentSync": SYNC_FULL,
+                "codeActionProvider": true,
+                "executeCommandProvider": {"commands": [ADD_TO_DICTIONARY]},
+            },
+            "serverInfo": {"name": "dym", "version": env!("CARGO_PKG_VERSION")},
+        })
+    }
+
+    /// Store the new text of a document, check it, and publish its diagnostics.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the document.
+    /// * `text` - The whole text of the document.
+    fn update(&mut self, uri: Option<&str>, text: Option<&str>) -> Result<(), Error> {
+        let (Some(uri), Some(text)) = (uri, text) else {
+            return Ok(());
+        };
+        self.documents.insert(
+            uri.to_owned(),
+            Document {
+                text: text.to_owned(),
+                misspellings: vec![],
+            },
+        );
+        self.check(uri)
+    }
+
+    /// Check the open document at `uri` and publish its diagnostics. The client is told if the
+    /// word list can't be loaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the document.
+    fn check(&mut self, uri: &str) -> Result<(), Error> {
+        let dictionary = match self.dictionaries.get(&self.lang) {
+            Ok(dictionary) => dictionary,
+            Err(error) => return show_error(&format!("dym: {}", error)),
+        };
+        let Some(document) = self.documents.get_mut(uri) else {
+            return Ok(());
+        };
+
+        let lines: Vec<&str> = document.text.lines().collect();
+        document.misspellings = check(&document.text, &dictionary, self.number)
+            .into_iter()
+            .map(|misspelling| {
+                let diagnostic = Diagnostic::new(uri, misspelling);
+                // Convert the column from characters to UTF-16 code units.
+                let line = lines[diagnostic.line - 1];
+                let start = utf16_len(line.chars().take(diagnostic.column - 1));
+                Misspelling {
+                    line: diagnostic.line - 1,
+                    start,
+                    end: start + utf16_len(diagnostic.word.chars()),
+                    message: diagnostic.message(),
+                    word: diagnostic.word,
+                    suggestions: diagnostic.suggestions,
+                }
+            })
+            .collect();
+
+        let diagnostics: Vec<Value> = document
+            .misspellings
+            .iter()
+            .map(|misspelling| {
+                json!({
+                    "range": misspelling.range(),
+                    "severity": SEVERITY_INFORMATION,
+                    "source": SOURCE,
+                    "message": misspelling.message,
+                })
+            })
+            .collect();
+        publish(uri, &diagnostics)
+    }
+
+    /// Return the code actions for the misspelled words touching the requested range: replacing
+    /// the word with each of its suggestions, best first, and adding it to the personal word list.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the `textDocument/codeAction` request.
+    fn code_actions(&self, params: &Value) -> Value {
+        let Some(uri) = params["textDocument"]["uri"].as_str() else {
+            return json!([]);
+        };
+        let Some(document) = self.documents.get(uri) else {
+            return json!([]);
+        };
+        let position = |name: &str| {
+            let position = &params["range"][name];
+            (
+                position["line"].as_u64().unwrap_or_default() as usize,
+                position["character"].as_u64().unwrap_or_default() as usize,
+            )
+        };
+        let (start, end) = (position("start"), position("end"));
+
+        let mut actions = vec![];
+        for misspelling in document.misspellings.iter().filter(|misspelling| {
+            (misspelling.line, misspelling.start) <= end
+                && start <= (misspelling.line, misspelling.end)
+        }) {
+            for (i, suggestion) in misspelling.suggestions.iter().enumerate() {
+                actions.push(json!({
+                    "title": format!("Replace with \"{}\"", suggestion),
+                    "kind": "quickfix",
+                    "isPreferred": i == 0,
+                    "edit": {
+                        "changes": {
+                            uri: [{"range": misspelling.range(), "newText": suggestion}],
+                        },
+                    },
+                }));
+            }
+            actions.push(json!({
+                "title": format!("Add \"{}\" to dictionary", misspelling.word),
+                "kind": "quickfix",
+                "command": {
+                    "title": format!("Add \"{}\" to dictionary", misspelling.word),
+                    "command": ADD_TO_DICTIONARY,
+                    "arguments": [misspelling.word],
+                },
+            }));
+        }
+        Value::Array(actions)
+    }
+
+    /// Run a command sent by the client. Adding a word to the dictionary writes it to the
+    /// personal word list and checks the open documents again.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - The parameters of the `workspace/executeCommand` request.
+    fn execute_command(&mut self, params: &Value) -> Result<Value, (i64, String)> {
+        let command = params["command"].as_str().unwrap_or_default();
+        if command != ADD_TO_DICTIONARY {
+            return Err((INVALID_PARAMS, format!("Unknown command {}", command)));
+        }
+        let words: Vec<&str> = params["arguments"]
+            .as_array()
+            .map(|arguments| arguments.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        if words.is_empty() {
+            return Err((INVALID_PARAMS, "Give the words to add".to_owned()));
+        }
+        self.dictionaries
+            .validate(&self.lang)
+            .map_err(|error| (INVALID_PARAMS, error.to_string()))?;
+
+        let add = || -> Result<(), Error> {
+            let (path, mut personal) = load_personal(&self.lang, self.dictionaries.data_dirs())?;
+            personal.extend(words.iter().copied());
+            personal.save(&path)
+        };
+        add().map_err(|error| (INVALID_PARAMS, error.to_string()))?;
+
+        let uris: Vec<String> = self.documents.keys().cloned().collect();
+        for uri in uris {
+            self.check(&uri)
+                .map_err(|error| (INVALID_PARAMS, error.to_string()))?;
+        }
+        Ok(Value::Null)
+    }
+}
+
+/// Read a JSON-RPC message framed with a `Content-Length` header. Return `None` once the input
+/// ends, and the parse error as the message if its body isn't valid JSON.
+///
+/// # Arguments
+///
+/// * `input` - The stream to read from.
+fn read_message(
+    input: &mut impl BufRead,
+) -> Result<Option<Result<Value, serde_json::Error>>, Error> {
+    let mut length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if input.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let length = length.ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidData,
+            "A message has no valid Content-Length",
+        )
+    })?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body)?;
+    Ok(Some(serde_json::from_slice(&body)))
+}
+
+/// Write a JSON-RPC message to standard output, framed with a `Content-Length` header.
+///
+/// # Arguments
+///
+/// * `message` - The message to send.
+fn send(message: &Value) -> Result<(), Error> {
+    let body = message.to_string();
+    let mut output = io::stdout().lock();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+/// Publish the diagnostics of a document, replacing the previous ones.
+///
+/// # Arguments
+///
+/// * `uri` - The URI of the document.
+/// * `diagnostics` - The diagnostics of the document.
+fn publish(uri: &str, diagnostics: &[Value]) -> Result<(), Error> {
+    send(&json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {"uri": uri, "diagnostics": diagnostics},
+    }))
+}
+
+/// Show an error message in the client.
+///
+/// # Arguments
+///
+/// * `message` - The message to show.
+fn show_error(message: &str) -> Result<(), Error> {
+    send(&json!({
+        "jsonrpc": "2.0",
+        "method": "window/showMessage",
+        "params": {"type": 1, "message": message},
+    }))
+}
+
+/// Return the number of UTF-16 code units needed to encode `chars`.
+///
+/// # Arguments
+///
+/// * `chars` - The characters to measure.
+fn utf16_len(chars: impl Iterator<Item = char>) -> usize {
+    chars.map(char::len_utf16).sum()
+}
@@ -1,36 +1,61 @@
+pub mod bundle;
+pub mod cache;
 pub mod cli;
-pub mod langs;
+pub mod cmd;
+pub mod config;
+#[cfg(unix)]
+pub mod daemon;
+// Without the download feature, a stand-in reports that word lists can't be downloaded.
+#[cfg_attr(not(feature = "download"), path = "offline.rs")]
+pub mod download;
+pub mod embedded;
+pub mod git_hook;
+pub mod interactive;
+pub mod lsp;
+pub mod repl;
+pub mod server;
+pub mod store;
+pub mod workers;
 
-use clap::Parser;
 use clap::error::ErrorKind;
+use clap::{CommandFactory, Parser};
 use colored::*;
 use dialoguer::{theme::ColorfulTheme, Select};
-use dirs::data_dir;
-use futures_util::StreamExt;
-use indicatif::{ProgressBar, ProgressStyle};
-use reqwest::get;
 use std::{
-    cmp::min,
-    fs::{create_dir, read_dir, read_to_string, remove_file, File},
-    io::{self, BufRead, Error, Write},
+    fs::{read_to_string, write},
+    io::{self, BufRead, Error, Read, Write},
+    path::{Path, PathBuf},
+    process,
 };
 
-use cli::Cli;
-use didyoumean::{edit_distance, insert_and_shift, yank};
-use langs::{LOCALES, SUPPORTED_LANGS};
+use cli::{
+    CheckArgs, Cli, Command, ConfigCommand, DictCommand, GitHookCommand, InteractiveArgs,
+    LangCommand, ReplArgs, SuggestArgs,
+};
+use config::Config;
+use didyoumean::{
+    check::check,
+    data::DataDirs,
+    dictionary::{Dictionary, Suggestion},
+    hunspell,
+    langs::{self, LOCALES, SUPPORTED_LANGS},
+    report::{self, Diagnostic},
+};
+use download::{Downloader, BASE_URL};
 
 fn main() {
     std::process::exit(match run_app() {
-        Ok(_) => 0,
+        Ok(code) => code,
         Err(error) => {
-            eprintln!("Error: {:?}", error);
+            eprintln!("Error: {}", error);
             1
         }
     });
 }
 
-/// Main function to run the application. Return `std::result::Result<(), std::io::Error>`.
-fn run_app() -> std::result::Result<(), Error> {
+/// Main function to run the application. Return the exit code of the program or an
+/// `std::io::Error`.
+fn run_app() -> std::result::Result<i32, Error> {
     // Correctly output ANSI escape codes on Windows.
     #[cfg(windows)]
     colored::control::set_virtual_terminal(true).ok();
@@ -38,124 +63,434 @@ fn run_app() -> std::result::Result<(), Error> {
     // Parse args using clap.
     let args = Cli::parse();
 
-    // Print all supported languages.
-    if args.print_langs {
-        println!("Supported Languages:");
-        let mut langs: Vec<String> = vec![];
-
-        // Add words to vector.
-        for key in SUPPORTED_LANGS.keys() {
-            langs.push(format!(" - {}: {}", key, SUPPORTED_LANGS.get(key).unwrap()));
-        }
-
-        // Sort and print vector.
-        langs.sort();
-        for lang in langs {
-            println!("{}", lang);
-        }
+    // Read the configuration file and resolve the directories holding the word lists.
+    let mut config = Config::load()?;
+    let data_dirs = DataDirs::new(args.data_dir, config.data_dir.clone()).with_hunspell_names(
+        config
+            .hunspell
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .collect(),
+    );
 
-        std::process::exit(0);
+    // Command line arguments take precedence over the configuration file.
+    if args.source_url.is_some() {
+        config.source_url = args.source_url;
     }
-
-    // Update all downloaded languages.
-    if args.update_langs {
-        update_langs();
-        std::process::exit(0);
+    if !args.mirrors.is_empty() {
+        config.mirrors = Some(args.mirrors);
+    }
+    if args.timeout.is_some() {
+        config.timeout = args.timeout;
+    }
+    if args.retries.is_some() {
+        config.retries = args.retries;
     }
 
-    let mut search_term = String::new();
+    // Map the deprecated flags to their subcommands.
+    let command = if args.print_langs {
+        Some(Command::Lang(LangCommand::List { installed: false }))
+    } else if args.update_langs {
+        Some(Command::Lang(LangCommand::Update))
+    } else {
+        args.command
+    };
 
-    // Check if nothing was passed in as the search term.
-    if args.search_term == None {
-        // Check if stdin is empty, produce error if so.
-        if atty::is(atty::Stream::Stdin) {
-            let mut cmd = clap::Command::new("dym [OPTIONS] <SEARCH_TERM>");
-            let error = cmd.error(
-                ErrorKind::MissingRequiredArgument,
-                format!(
-                    "The {} argument was not provided.\n\n\tEither provide it as an argument or pass it in from standard input.",
-                    "<SEARCH_TERM>".green()
-                )
-            );
-            clap::Error::exit(&error);
-        } else {
-            // Read search_term from standard input if stdin is not empty.
-            let stdin = io::stdin();
-            stdin.lock().read_line(&mut search_term).unwrap();
+    match command {
+        None => suggest(args.suggest, &config, &data_dirs),
+        Some(Command::Suggest(suggest_args)) => suggest(suggest_args, &config, &data_dirs),
+        Some(Command::Check(check_args)) => check_files(check_args, &config, &data_dirs),
+        Some(Command::Interactive(InteractiveArgs {
+            query,
+            number,
+            lang,
+        })) => interactive::run(
+            query.as_deref().unwrap_or_default(),
+            lang.lang.as_deref().unwrap_or(config.lang()),
+            number.unwrap_or(config.number()),
+            &config,
+            &data_dirs,
+        ),
+        Some(Command::Lang(LangCommand::List { installed: false })) => {
+            print_langs();
+            Ok(0)
         }
-    } else {
-        // Unwrap Option<String> that was read from the client.
-        search_term = args.search_term.unwrap();
+        Some(Command::Lang(LangCommand::List { installed: true })) => {
+            print_installed_langs(&data_dirs)?;
+            Ok(0)
+        }
+        Some(Command::Lang(LangCommand::Install { langs })) => {
+            install_langs(&langs, &config, &data_dirs)?;
+            Ok(0)
+        }
+        Some(Command::Lang(LangCommand::Update)) => {
+            Downloader::new(&config).update_langs(&data_dirs)
+        }
+        Some(Command::Lang(LangCommand::Remove { langs })) => remove_langs(&langs, &data_dirs),
+        Some(Command::Lang(LangCommand::Export { file, langs })) => {
+            export_langs(&file, &langs, &data_dirs)?;
+            Ok(0)
+        }
+        Some(Command::Lang(LangCommand::Import { file })) => {
+            import_langs(&file, &data_dirs)?;
+            Ok(0)
+        }
+        Some(Command::Repl(ReplArgs {
+            number,
+            clean_output,
+            verbose,
+            lang,
+        })) => repl::run(
+            lang.lang.as_deref().unwrap_or(config.lang()),
+            number.unwrap_or(config.number()),
+            clean_output,
+            verbose,
+            &config,
+            &data_dirs,
+        ),
+        Some(Command::Daemon { langs, socket }) => run_daemon(langs, socket, config, data_dirs),
+        Some(Command::Serve { langs, bind }) => {
+            let langs = preload_langs(langs, &config, &data_dirs)?;
+            server::serve(&bind, &langs, config, data_dirs)
+        }
+        Some(Command::Lsp { number, lang }) => {
+            let lang = lang.lang.unwrap_or_else(|| config.lang().to_owned());
+            lsp::run(&lang, number, config, data_dirs)
+        }
+        Some(Command::Cmd {
+            hook: Some(shell),
+            prompt,
+            ..
+        }) => {
+            print!("{}", cmd::hook(&shell, prompt));
+            Ok(0)
+        }
+        Some(Command::Cmd {
+            name,
+            aliases,
+            number,
+            prompt,
+            ..
+        }) => cmd::run(
+            &name.unwrap_or_default(),
+            &aliases,
+            number.unwrap_or(config.number()),
+            prompt,
+        ),
+        Some(Command::GitHook(GitHookCommand::CommitMsg {
+            file,
+            strict,
+            number,
+            lang,
+        })) => {
+            let lang = lang.lang.unwrap_or_else(|| config.lang().to_owned());
+            git_hook::commit_msg(&file, strict, number, &lang, &config, &data_dirs)
+        }
+        Some(Command::GitHook(GitHookCommand::Install { strict, force })) => {
+            git_hook::install(strict, force)
+        }
+        Some(Command::Dict(dict_command)) => dict(dict_command, &config, &data_dirs),
+        Some(Command::Config(config_command)) => configure(config_command, &config, &data_dirs),
     }
+}
 
-    if SUPPORTED_LANGS.contains_key(args.lang.as_str()) {
-        fetch_word_list(args.lang.to_owned());
-    } else {
-        // Not supported
-        // Initialize new command.
-        let mut cmd = clap::Command::new("dym [OPTIONS] <SEARCH_TERM>");
+/// Print the suggestions for a single word, optionally letting the user copy one of them to the
+/// clipboard.
+///
+/// # Arguments
+///
+/// * `args` - The arguments of the `suggest` subcommand.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+fn suggest(args: SuggestArgs, config: &Config, data_dirs: &DataDirs) -> Result<i32, Error> {
+    let number = args.number.unwrap_or(config.number());
+    let lang = args.lang.lang.as_deref().unwrap_or(config.lang());
+    let mut search_term = String::new();
 
-        // Whether or not locale code is valid.
-        let error_string = if LOCALES.contains_key(args.lang.as_str()) {
+    if let Some(term) = &args.search_term {
+        // Use the search term that was read from the client.
+        search_term = term.to_owned();
+    } else if atty::is(atty::Stream::Stdin) {
+        // Nothing was passed in as the search term and stdin is empty, produce error.
+        let error = Cli::command().error(
+            ErrorKind::MissingRequiredArgument,
             format!(
-                "There is currently no word list for {}",
-                LOCALES.get(args.lang.as_str()).cloned().unwrap()
+                "The {} argument was not provided.\n\n\tEither provide it as an argument or pass it in from standard input.",
+                "<SEARCH_TERM>".green()
             )
-        } else {
-            format!("{} is not a recognized localed code", args.lang)
-        };
+        );
+        clap::Error::exit(&error);
+    } else {
+        // Read search_term from standard input if stdin is not empty.
+        let stdin = io::stdin();
+        stdin.lock().read_line(&mut search_term)?;
+    }
+    let search_term = search_term.trim();
 
-        // Set error.
-        let error = cmd.error(ErrorKind::MissingRequiredArgument, error_string);
+    // Get the closest words from the daemon if it is running, or from the dictionary.
+    let remote = query_daemon(search_term, lang, number, config, data_dirs);
+    let dictionary;
+    let suggestions = match &remote {
+        Some(remote) => remote
+            .iter()
+            .map(|(word, distance)| Suggestion {
+                word,
+                distance: *distance,
+            })
+            .collect(),
+        None => {
+            dictionary = load_dictionary(lang, config, data_dirs)?;
+            dictionary.suggest(search_term, number)
+        }
+    };
 
-        // Exit with error.
-        clap::Error::exit(&error);
+    // Words are chosen when they are sent somewhere. Picking a word without saying where prints it.
+    let print = args.print
+        || (args.pick.is_some() && !args.yank && args.output.is_none() && !args.type_word);
+    let choose = print || args.yank || args.output.is_some() || args.type_word;
+
+    // Pick the word without printing anything else so the output can be used by scripts.
+    if let Some(pick) = args.pick {
+        let suggestion = pick
+            .checked_sub(1)
+            .and_then(|index| suggestions.get(index))
+            .ok_or_else(|| {
+                Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "There is no suggestion {}, only {} were found",
+                        pick,
+                        suggestions.len()
+                    ),
+                )
+            })?;
+        send_word(suggestion.word, &args, print, config, &mut io::sink())?;
+        return Ok(0);
     }
 
-    // Get word list. The program will only get here if/when this is a valid word list.
-    let word_list = read_to_string(dirs::data_dir().unwrap().join("didyoumean").join(args.lang))
-        .expect("Error reading file");
-
-    // Get dictionary of words from words.txt.
-    let dictionary = word_list.split('\n');
-
-    // Create mutable vecs for storing the top n words.
-    let mut top_n_words = vec![""; args.number];
-    let mut top_n_dists = vec![search_term.len() * 10; args.number];
-
-    // Loop over the words in the dictionary, run the algorithm, and
-    // add to the list if appropriate
-    let search_chars = search_term.chars().collect::<Vec<_>>();
-    for word in dictionary {
-        // Get edit distance.
-        let dist = edit_distance(&search_chars, word);
-
-        // Add to the list if appropriate.
-        if dist < top_n_dists[args.number - 1] {
-            for i in 0..args.number {
-                if dist < top_n_dists[i] {
-                    insert_and_shift(&mut top_n_dists, i, dist);
-                    insert_and_shift(&mut top_n_words, i, word);
-                    break;
+    // Keep standard output for the chosen word if it is printed.
+    let mut out: Box<dyn Write> = if print {
+        Box::new(io::stderr())
+    } else {
+        Box::new(io::stdout())
+    };
+
+    // Print out results.
+    write_heading(&mut out, search_term, &suggestions, args.clean_output)?;
+    let items = format_suggestions(&suggestions, number, args.clean_output, args.verbose);
+
+    // If the word is sent somewhere, let the user choose it.
+    if choose {
+        // Print prompt
+        writeln!(
+            out,
+            "{} {}",
+            "?".yellow(),
+            "[↑↓ to move, ↵ to select, esc/q to cancel]".bold()
+        )?;
+        // Get the chosen argument.
+        let chosen = Select::with_theme(&ColorfulTheme::default())
+            .items(&items)
+            .default(0)
+            .interact_opt()
+            .map_err(|dialoguer::Error::IO(error)| {
+                if error.kind() == io::ErrorKind::NotConnected {
+                    Error::new(
+                        error.kind(),
+                        "Choosing a suggestion needs a terminal, pass --pick to choose one without it",
+                    )
+                } else {
+                    error
                 }
+            })?;
+
+        // Print out items since dialoguer clears.
+        for item in items {
+            writeln!(out, "  {}", item)?;
+        }
+
+        match chosen {
+            // If the chosen arguemnt is valid.
+            Some(index) => send_word(suggestions[index].word, &args, print, config, &mut out)?,
+            // If no argument is chosen.
+            None => {
+                writeln!(out, "{}", "No selection made".red())?;
+                return Ok(1);
             }
         }
+    } else {
+        // If no word is chosen, print out all the items.
+        for item in items {
+            writeln!(out, "{}", item)?;
+        }
     }
 
-    // Print out results.
-    if !args.clean_output {
-        if top_n_dists[0] == 0 {
-            println!("{} is spelled correctly\n", search_term.bold().green());
+    Ok(0)
+}
+
+/// Ask a running daemon for the suggestions for `search_term`. Return `None` if no daemon is
+/// running or it couldn't answer.
+///
+/// # Arguments
+///
+/// * `search_term` - The word to suggest corrections for.
+/// * `lang` - The locale code of the word list.
+/// * `number` - The number of suggestions.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+#[cfg(unix)]
+fn query_daemon(
+    search_term: &str,
+    lang: &str,
+    number: usize,
+    config: &Config,
+    data_dirs: &DataDirs,
+) -> Option<Vec<(String, usize)>> {
+    let socket = config.socket(data_dirs)?;
+    let suggestions = daemon::suggest(&socket, search_term, lang, number)?;
+    Some(
+        suggestions
+            .into_iter()
+            .map(|suggestion| (suggestion.word, suggestion.distance))
+            .collect(),
+    )
+}
+
+/// Return `None`, as the daemon is only available on Unix.
+#[cfg(not(unix))]
+fn query_daemon(
+    _search_term: &str,
+    _lang: &str,
+    _number: usize,
+    _config: &Config,
+    _data_dirs: &DataDirs,
+) -> Option<Vec<(String, usize)>> {
+    None
+}
+
+/// Run the daemon on `socket`, or the configured socket, loading the word lists of `langs`.
+///
+/// # Arguments
+///
+/// * `langs` - The locale codes of the word lists to load right away.
+/// * `socket` - The path of the socket given on the command line.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+#[cfg(unix)]
+fn run_daemon(
+    langs: Vec<String>,
+    socket: Option<PathBuf>,
+    config: Config,
+    data_dirs: DataDirs,
+) -> Result<i32, Error> {
+    let langs = preload_langs(langs, &config, &data_dirs)?;
+    let socket = socket
+        .or_else(|| config.socket(&data_dirs))
+        .ok_or_else(|| {
+            Error::new(
+                io::ErrorKind::NotFound,
+                "No data directory could be determined. Set the socket configuration key",
+            )
+        })?;
+    daemon::serve(&socket, &langs, config, data_dirs)
+}
+
+/// Return the languages a long-running command loads at startup, the configured language if
+/// none are given, downloading the ones that aren't installed as it never downloads them later.
+///
+/// # Arguments
+///
+/// * `langs` - The locale codes given on the command line.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+fn preload_langs(
+    langs: Vec<String>,
+    config: &Config,
+    data_dirs: &DataDirs,
+) -> Result<Vec<String>, Error> {
+    let langs = if langs.is_empty() {
+        vec![config.lang().to_owned()]
+    } else {
+        langs
+    };
+    // Languages answered from a Hunspell dictionary have no word list to validate or install.
+    let word_lists: Vec<&String> = langs
+        .iter()
+        .filter(|lang| hunspell_dictionary(lang, data_dirs).is_none())
+        .collect();
+    for lang in &word_lists {
+        validate_lang(lang);
+    }
+    let missing: Vec<String> = word_lists
+        .into_iter()
+        .filter(|lang| data_dirs.find(lang).is_none() && embedded::size(lang).is_none())
+        .cloned()
+        .collect();
+    if !missing.is_empty() {
+        install_langs(&missing, config, data_dirs)?;
+    }
+    Ok(langs)
+}
+
+/// Return an error, as the daemon is only available on Unix.
+#[cfg(not(unix))]
+fn run_daemon(
+    _langs: Vec<String>,
+    _socket: Option<PathBuf>,
+    _config: Config,
+    _data_dirs: DataDirs,
+) -> Result<i32, Error> {
+    Err(Error::new(
+        io::ErrorKind::Unsupported,
+        "dym daemon is only available on Unix",
+    ))
+}
+
+/// Write the title printed above the suggestions for `search_term`, unless the output is clean.
+///
+/// # Arguments
+///
+/// * `out` - Where to write the title.
+/// * `search_term` - The word the suggestions are for.
+/// * `suggestions` - The suggestions for `search_term`.
+/// * `clean_output` - Whether to leave the title out.
+fn write_heading(
+    out: &mut dyn Write,
+    search_term: &str,
+    suggestions: &[Suggestion],
+    clean_output: bool,
+) -> Result<(), Error> {
+    if !clean_output {
+        if suggestions.first().is_some_and(|first| first.distance == 0) {
+            writeln!(out, "{} is spelled correctly\n", search_term.bold().green())?;
         }
-        println!("{}", "Did you mean?".blue().bold());
+        writeln!(out, "{}", "Did you mean?".blue().bold())?;
     }
-    let mut items = vec!["".to_string(); args.number];
-    for i in 0..args.number {
+    Ok(())
+}
+
+/// Format each suggestion as a line of the list of suggestions.
+///
+/// # Arguments
+///
+/// * `suggestions` - The suggestions to format.
+/// * `number` - The number of suggestions asked for, which sets the width of the numbers.
+/// * `clean_output` - Whether to leave out the numbers and colour.
+/// * `verbose` - Whether to add the edit distance of each suggestion.
+fn format_suggestions(
+    suggestions: &[Suggestion],
+    number: usize,
+    clean_output: bool,
+    verbose: bool,
+) -> Vec<String> {
+    let mut items = vec![];
+    for (i, suggestion) in suggestions.iter().enumerate() {
         let mut output: String = "".to_string();
-        let indent = args.number.to_string().len();
+        let indent = number.to_string().len();
 
         // Add numbers if not clean.
-        if !args.clean_output {
+        if !clean_output {
             output.push_str(&format!(
                 "{:>indent$}{} ",
                 (i + 1).to_string().purple(),
@@ -164,145 +499,657 @@ fn run_app() -> std::result::Result<(), Error> {
         }
 
         // Add words in order of edit distance.
-        output.push_str(top_n_words[i]);
+        output.push_str(suggestion.word);
 
         // Add edit distance if verbose.
-        if args.verbose {
-            output.push_str(&format!(" (edit distance: {})", top_n_dists[i]));
+        if verbose {
+            output.push_str(&format!(" (edit distance: {})", suggestion.distance));
         }
 
         // Print concatenated string.
-        items[i] = output;
+        items.push(output);
     }
+    items
+}
 
-    // If the yank argument is set, copy the item to the clipboard.
+/// Send the chosen word to every output requested on the command line, reporting each one to
+/// `report`.
+///
+/// # Arguments
+///
+/// * `word` - The chosen word.
+/// * `args` - The arguments of the `suggest` subcommand.
+/// * `print` - Whether to print the word to standard output.
+/// * `config` - The configuration file.
+/// * `report` - Where to confirm that the word was sent.
+fn send_word(
+    word: &str,
+    args: &SuggestArgs,
+    print: bool,
+    config: &Config,
+    report: &mut dyn Write,
+) -> Result<(), Error> {
+    if print {
+        println!("{}", word);
+    }
     if args.yank {
-        // Print prompt
-        println!(
-            "{} {}",
-            "?".yellow(),
-            "[↑↓ to move, ↵ to select, esc/q to cancel]".bold()
+        copy_to_clipboard(word, config)?;
+        writeln!(
+            report,
+            "{}",
+            format!("\"{}\" copied to clipboard", word).green()
+        )?;
+    }
+    if let Some(path) = &args.output {
+        write(path, format!("{}\n", word))?;
+        writeln!(
+            report,
+            "{}",
+            format!("\"{}\" written to {}", word, path.display()).green()
+        )?;
+    }
+    if args.type_word {
+        type_word(word, config)?;
+    }
+
+    Ok(())
+}
+
+/// Type `word` into the focused window with the command in the `type_command` configuration key.
+///
+/// # Arguments
+///
+/// * `word` - The word to type.
+/// * `config` - The configuration file.
+fn type_word(word: &str, config: &Config) -> Result<(), Error> {
+    let args = config.type_command().args()?;
+    let (program, args) = args.split_first().ok_or_else(|| {
+        Error::new(
+            io::ErrorKind::InvalidInput,
+            "The type_command configuration key is empty",
+        )
+    })?;
+
+    let status = process::Command::new(program)
+        .args(args)
+        .arg(word)
+        .stdin(process::Stdio::null())
+        .status()
+        .map_err(|error| {
+            Error::new(error.kind(), format!("{} can't be run: {}", program, error))
+        })?;
+    if !status.success() {
+        return Err(Error::other(format!("{} failed with {}", program, status)));
+    }
+
+    Ok(())
+}
+
+/// Copy `text` to the clipboard chosen with the `clipboard` configuration key.
+///
+/// # Arguments
+///
+/// * `text` - The text to copy.
+/// * `config` - The configuration file.
+#[cfg(feature = "clipboard")]
+fn copy_to_clipboard(text: &str, config: &Config) -> Result<(), Error> {
+    didyoumean::clipboard::yank(
+        text,
+        config.clipboard.unwrap_or_default(),
+        &config.clipboard_options(),
+    )
+}
+
+/// Return an error, as dym was built without clipboard support.
+///
+/// # Arguments
+///
+/// * `text` - The text to copy.
+/// * `config` - The configuration file.
+#[cfg(not(feature = "clipboard"))]
+fn copy_to_clipboard(_text: &str, _config: &Config) -> Result<(), Error> {
+    Err(Error::new(
+        io::ErrorKind::Unsupported,
+        "dym was built without the clipboard feature",
+    ))
+}
+
+/// Print the misspelled words in the given files, or standard input, with suggestions. Return 1
+/// if any misspelled words were found.
+///
+/// # Arguments
+///
+/// * `args` - The arguments of the `check` subcommand.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+fn check_files(args: CheckArgs, config: &Config, data_dirs: &DataDirs) -> Result<i32, Error> {
+    let lang = args.lang.lang.as_deref().unwrap_or(config.lang());
+    let dictionary = load_dictionary(lang, config, data_dirs)?;
+
+    // Read the text to check, labelled with where it came from.
+    let mut inputs = vec![];
+    if args.files.is_empty() {
+        let mut text = String::new();
+        io::stdin().read_to_string(&mut text)?;
+        inputs.push((report::STDIN.to_owned(), text));
+    }
+    for file in &args.files {
+        inputs.push((file.display().to_string(), read_to_string(file)?));
+    }
+
+    let diagnostics: Vec<Diagnostic> = inputs
+        .iter()
+        .flat_map(|(name, text)| {
+            check(text, &dictionary, args.number)
+                .into_iter()
+                .map(|misspelling| Diagnostic::new(name, misspelling))
+        })
+        .collect();
+    report::write(&mut io::stdout(), args.format, &diagnostics)?;
+
+    Ok(if diagnostics.is_empty() { 0 } else { 1 })
+}
+
+/// Add to, remove from, or print the personal word list.
+///
+/// # Arguments
+///
+/// * `command` - The `dict` subcommand to run.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+fn dict(command: DictCommand, config: &Config, data_dirs: &DataDirs) -> Result<i32, Error> {
+    let lang = match &command {
+        DictCommand::Add { lang, .. }
+        | DictCommand::Remove { lang, .. }
+        | DictCommand::List { lang } => lang.lang.clone().unwrap_or(config.lang().to_owned()),
+    };
+    if !LOCALES.contains_key(&lang) {
+        let error = Cli::command().error(
+            ErrorKind::InvalidValue,
+            format!("{} is not a recognized localed code", lang),
         );
-        // Get the chosen argument.
-        let chosen = Select::with_theme(&ColorfulTheme::default())
-            .items(&items)
-            .default(0)
-            .interact_opt()
-            .unwrap();
+        clap::Error::exit(&error);
+    }
 
-        // Print out items since dialoguer clears.
-        for item in items {
-            println!("  {}", item);
-        }
+    let (path, mut personal) = load_personal(&lang, data_dirs)?;
 
-        match chosen {
-            // If the chosen arguemnt is valid.
-            Some(index) => {
-                yank(top_n_words[index]);
-                println!(
-                    "{}",
-                    format!("\"{}\" copied to clipboard", top_n_words[index]).green()
-                );
-            }
-            // If no argument is chosen.
-            None => {
-                println!("{}", "No selection made".red());
-                std::process::exit(1);
+    match &command {
+        DictCommand::Add { words, .. } => {
+            let before = personal.len();
+            personal.extend(words.iter().map(String::as_str));
+            personal.save(&path)?;
+            println!(
+                "Added {} words to the personal {} word list",
+                personal.len() - before,
+                LOCALES.get(&lang).unwrap().blue()
+            );
+        }
+        DictCommand::Remove { words, .. } => {
+            let removed = words.iter().filter(|word| personal.remove(word)).count();
+            personal.save(&path)?;
+            println!(
+                "Removed {} words from the personal {} word list",
+                removed,
+                LOCALES.get(&lang).unwrap().blue()
+            );
+        }
+        DictCommand::List { .. } => {
+            for word in personal.words() {
+                println!("{}", word);
             }
         }
+    }
+
+    Ok(0)
+}
+
+/// Return the path of the personal word list for `lang` and its contents, which are empty if it
+/// doesn't exist yet.
+///
+/// # Arguments
+///
+/// * `lang` - The locale code of the personal word list.
+/// * `data_dirs` - The directories holding the word lists.
+fn load_personal(lang: &str, data_dirs: &DataDirs) -> Result<(PathBuf, Dictionary), Error> {
+    data_dirs.ensure_user()?;
+    let path = data_dirs.personal(lang).unwrap();
+    let personal = if path.is_file() {
+        Dictionary::load(&path)?
     } else {
-        // If yank is not set, print out all the items.
-        for item in items {
-            println!("{}", item);
+        Dictionary::new()
+    };
+    Ok((path, personal))
+}
+
+/// Print or change the configuration file.
+///
+/// # Arguments
+///
+/// * `command` - The `config` subcommand to run.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+fn configure(command: ConfigCommand, config: &Config, data_dirs: &DataDirs) -> Result<i32, Error> {
+    match command {
+        ConfigCommand::Path => match Config::path() {
+            Some(path) => println!("{}", path.display()),
+            None => println!("{}", "No configuration directory found".red()),
+        },
+        ConfigCommand::Show => {
+            // Fill in the defaults so the effective value of every key is shown.
+            let effective = Config {
+                lang: Some(config.lang().to_owned()),
+                number: Some(config.number()),
+                data_dir: data_dirs.user().map(Path::to_path_buf),
+                source_url: Some(config.source_url.as_deref().unwrap_or(BASE_URL).to_owned()),
+                mirrors: config.mirrors.clone(),
+                timeout: Some(config.timeout().as_secs()),
+                retries: Some(config.retries()),
+                proxy: config.proxy.clone(),
+                compression: Some(config.compression.unwrap_or_default()),
+                socket: config.socket(data_dirs),
+                hunspell: config.hunspell.clone(),
+                cors_origins: config.cors_origins.clone(),
+                type_command: Some(config.type_command()),
+                #[cfg(feature = "clipboard")]
+                clipboard: Some(config.clipboard.unwrap_or_default()),
+                #[cfg(feature = "clipboard")]
+                clipboard_selection: Some(config.clipboard_options().selection),
+                #[cfg(feature = "clipboard")]
+                clipboard_lifetime: Some(config.clipboard_options().lifetime.as_secs()),
+                #[cfg(not(feature = "clipboard"))]
+                clipboard: config.clipboard.clone(),
+                #[cfg(not(feature = "clipboard"))]
+                clipboard_selection: config.clipboard_selection.clone(),
+                #[cfg(not(feature = "clipboard"))]
+                clipboard_lifetime: config.clipboard_lifetime.clone(),
+            };
+            print!("{}", toml::to_string(&effective).unwrap());
         }
+        ConfigCommand::Set { key, value } => Config::set(&key, &value)?,
+        ConfigCommand::Unset { key } => Config::unset(&key)?,
     }
 
-    Ok(())
+    Ok(0)
+}
+
+/// Print all supported languages.
+fn print_langs() {
+    println!("Supported Languages:");
+    let mut langs: Vec<String> = vec![];
+
+    // Add words to vector.
+    for key in SUPPORTED_LANGS.keys() {
+        langs.push(format!(" - {}: {}", key, SUPPORTED_LANGS.get(key).unwrap()));
+    }
+
+    // Sort and print vector.
+    langs.sort();
+    for lang in langs {
+        println!("{}", lang);
+    }
 }
 
-/// Fetch the word list specified by `lang` from https://github.com/hisbaan/wordlists
+/// Print the installed languages along with the size and modification date of their word lists.
 ///
 /// # Arguments
 ///
-/// * `lang` - A locale code string to define the word list file to fetch.
-#[tokio::main]
-async fn fetch_word_list(lang: String) {
-    // Get data directory.
-    let data_dir = dirs::data_dir().unwrap().join("didyoumean");
+/// * `data_dirs` - The directories holding the word lists.
+fn print_installed_langs(data_dirs: &DataDirs) -> Result<(), Error> {
+    println!("Installed Languages:");
+    let mut langs: Vec<&str> = SUPPORTED_LANGS
+        .keys()
+        .chain(LOCALES.keys())
+        .cloned()
+        .collect();
+    langs.sort();
+    langs.dedup();
+
+    for lang in langs {
+        // Only the word list that would be used is listed, even if it is shadowed elsewhere.
+        if let Some(path) = data_dirs.find(lang) {
+            let metadata = path.metadata()?;
+            let modified = humantime::format_rfc3339_seconds(metadata.modified()?).to_string();
+            let system = if data_dirs.user().is_some_and(|user| path.starts_with(user)) {
+                ""
+            } else {
+                ", system"
+            };
 
-    // Create data directory if it doesn't exist.
-    if !data_dir.is_dir() {
-        create_dir(data_dir).expect("Failed to create data directory");
+            println!(
+                " - {}: {} ({}, modified {}{})",
+                lang,
+                langs::name(lang).unwrap(),
+                format_size(metadata.len()),
+                &modified[..10],
+                system
+            );
+        } else if let Some(size) = embedded::size(lang) {
+            println!(
+                " - {}: {} ({}, embedded)",
+                lang,
+                SUPPORTED_LANGS.get(lang).unwrap(),
+                format_size(size as u64)
+            );
+        } else if let Some(path) = hunspell_dictionary(lang, data_dirs) {
+            println!(
+                " - {}: {} ({}, Hunspell dictionary {})",
+                lang,
+                LOCALES.get(lang).unwrap(),
+                format_size(path.metadata()?.len()),
+                path.display()
+            );
+        }
     }
 
-    // Get file path.
-    let file_path = dirs::data_dir().unwrap().join("didyoumean").join(&lang);
+    Ok(())
+}
 
-    // If the file does not exist, fetch it from the server.
-    if !file_path.is_file() {
-        println!(
-            "Downloading {} word list...",
-            LOCALES.get(&lang).unwrap().to_string().blue()
-        );
+/// Format `bytes` with a binary unit, such as `1.50 KiB`.
+///
+/// # Arguments
+///
+/// * `bytes` - The size to format.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
 
-        let url = format!(
-            "https://raw.githubusercontent.com/hisbaan/wordlists/main/{}",
-            &lang
-        );
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
 
-        // Setup reqwest.
-        let response = get(&url).await.expect("Request failed");
-        let total_size = response.content_length().unwrap();
-        let mut file = File::create(file_path).expect("Failed to create file");
-        let mut downloaded: u64 = 0;
-        let mut stream = response.bytes_stream();
-
-        // Setup indicatif.
-        let pb = ProgressBar::new(total_size);
-        pb.set_style(
-            ProgressStyle::default_bar()
-                .template(
-                    "[{elapsed_precise}] [{wide_bar:.blue/cyan}] {bytes}/{total_bytes} ({eta})",
-                )
-                .unwrap()
-                .progress_chars("#>-"),
-        );
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.2} {}", size, UNITS[unit])
+}
 
-        // Read from stream into file.
-        while let Some(item) = stream.next().await {
-            let chunk = item.expect("Error downloading file");
-            file.write_all(&chunk).expect("Error while writing to file");
-            let new = min(downloaded + (chunk.len() as u64), total_size);
-            downloaded = new;
-            pb.set_position(new);
+/// Download the word lists for `langs` that aren't installed yet.
+///
+/// # Arguments
+///
+/// * `langs` - The locale codes of the word lists to install.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+fn install_langs(langs: &[String], config: &Config, data_dirs: &DataDirs) -> Result<(), Error> {
+    // Check every language before downloading anything.
+    for lang in langs {
+        validate_lang(lang);
+    }
+
+    let mut missing = vec![];
+    for lang in langs {
+        if data_dirs.find(lang).is_some() {
+            println!(
+                "{} word list is already installed",
+                LOCALES.get(lang).unwrap().blue()
+            );
+        } else if embedded::size(lang).is_some() {
+            println!(
+                "{} word list is embedded in dym",
+                LOCALES.get(lang).unwrap().blue()
+            );
+        } else {
+            missing.push(lang.to_owned());
         }
+    }
+
+    // Download the remaining word lists concurrently.
+    Downloader::new(config).fetch_word_lists(&missing, data_dirs)
+}
+
+/// Pack the installed word lists for `langs` into the bundle `file`.
+///
+/// # Arguments
+///
+/// * `file` - The path to write the bundle to.
+/// * `langs` - The locale codes of the word lists to export, or all installed ones if empty.
+/// * `data_dirs` - The directories holding the word lists.
+fn export_langs(file: &Path, langs: &[String], data_dirs: &DataDirs) -> Result<(), Error> {
+    for lang in langs {
+        validate_lang(lang);
+    }
+
+    for lang in bundle::export(file, langs, data_dirs)? {
+        println!("Exported {} word list", LOCALES.get(&lang).unwrap().blue());
+    }
+
+    Ok(())
+}
+
+/// Install the word lists in the bundle `file` into the user data directory.
+///
+/// # Arguments
+///
+/// * `file` - The path of the bundle.
+/// * `data_dirs` - The directories holding the word lists.
+fn import_langs(file: &Path, data_dirs: &DataDirs) -> Result<(), Error> {
+    for lang in bundle::import(file, data_dirs)? {
+        println!("Imported {} word list", LOCALES.get(&lang).unwrap().blue());
+    }
+
+    Ok(())
+}
 
-        // Print completed bar.
-        pb.finish();
+/// Delete the downloaded word lists for `langs` from the user data directory. Return 1 if any of
+/// them were not installed there.
+///
+/// # Arguments
+///
+/// * `langs` - The locale codes of the word lists to remove.
+/// * `data_dirs` - The directories holding the word lists.
+fn remove_langs(langs: &[String], data_dirs: &DataDirs) -> Result<i32, Error> {
+    let mut code = 0;
+
+    for lang in langs {
+        let name = LOCALES.get(lang).map_or(lang.as_str(), |name| name);
+        let path = data_dirs.user().map(|dir| dir.join(lang));
+
+        if let Some(path) = path.filter(|path| path.is_file()) {
+            store::remove(&path)?;
+            println!("Removed {} word list", name.blue());
+        } else if let Some(path) = data_dirs.find(lang) {
+            println!(
+                "{}",
+                format!(
+                    "{} word list is installed system-wide in {} and can't be removed",
+                    name,
+                    path.display()
+                )
+                .red()
+            );
+            code = 1;
+        } else {
+            println!("{}", format!("{} word list is not installed", name).red());
+            code = 1;
+        }
     }
+
+    Ok(code)
 }
 
-/// Update the word list files by deleting and downloading the files from the repository.
-fn update_langs() {
-    let data = data_dir().unwrap().join("didyoumean");
+/// Exit with an error if there is no word list for `lang`.
+///
+/// # Arguments
+///
+/// * `lang` - The locale code to validate.
+fn validate_lang(lang: &str) {
+    if !SUPPORTED_LANGS.contains_key(lang) {
+        // Whether or not locale code is valid.
+        let error_string = if LOCALES.contains_key(lang) {
+            format!(
+                "There is currently no word list for {}, install a Hunspell dictionary to use it",
+                LOCALES.get(lang).cloned().unwrap()
+            )
+        } else {
+            format!("{} is not a recognized localed code", lang)
+        };
 
-    // Create data directory if it doesn't exist.
-    if !data.is_dir() {
-        create_dir(&data).expect("Failed to create data directory");
+        // Exit with error.
+        let error = Cli::command().error(ErrorKind::InvalidValue, error_string);
+        clap::Error::exit(&error);
     }
+}
+
+/// Return the path of the Hunspell dictionary used for `lang`. Hunspell dictionaries are only used
+/// for languages that have no word list to download.
+///
+/// # Arguments
+///
+/// * `lang` - The locale code of the language.
+/// * `data_dirs` - The directories holding the word lists.
+fn hunspell_dictionary(lang: &str, data_dirs: &DataDirs) -> Option<PathBuf> {
+    if SUPPORTED_LANGS.contains_key(lang) || !LOCALES.contains_key(lang) {
+        return None;
+    }
+    data_dirs.find_hunspell(lang)
+}
 
-    // Get files in data directory.
-    let data_dir_files = read_dir(&data).unwrap();
+/// Load the word list for `lang`, from the binary if it is embedded and not installed, from a
+/// Hunspell dictionary if there is no word list for `lang`, or downloading it if necessary, along
+/// with the personal word list.
+///
+/// # Arguments
+///
+/// * `lang` - The locale code of the word list.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+fn load_dictionary(lang: &str, config: &Config, data_dirs: &DataDirs) -> Result<Dictionary, Error> {
+    load_dictionary_with(lang, data_dirs, || load_installed(lang, config, data_dirs))
+}
 
-    // Delete and update all files.
-    for file in data_dir_files {
-        let file_name = file.unwrap().file_name();
-        let string: &str = file_name.to_str().unwrap();
+/// Load the dictionary for `lang` like `load_dictionary`, but without ever downloading or
+/// printing anything. Return a `NotFound` error if the word list isn't installed and an
+/// `InvalidData` error if it is corrupt.
+///
+/// # Arguments
+///
+/// * `lang` - The locale code of the word list.
+/// * `data_dirs` - The directories holding the word lists.
+fn load_offline(lang: &str, data_dirs: &DataDirs) -> Result<Dictionary, Error> {
+    load_dictionary_with(lang, data_dirs, || {
+        let word_list_path = data_dirs.find(lang).ok_or_else(|| {
+            Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} word list is not installed", lang),
+            )
+        })?;
+        read_installed(&word_list_path).map_err(|error| {
+            if error.kind() != io::ErrorKind::InvalidData {
+                return error;
+            }
+            Error::new(
+                error.kind(),
+                format!(
+                    "The {} word list is corrupt ({}), install it again with `dym lang install {}`",
+                    LOCALES.get(lang).unwrap(),
+                    error,
+                    lang
+                ),
+            )
+        })
+    })
+}
+
+/// Load the dictionary for `lang` from a Hunspell dictionary or the embedded word list, or with
+/// `installed` otherwise, along with the personal word list.
+///
+/// # Arguments
+///
+/// * `lang` - The locale code of the word list.
+/// * `data_dirs` - The directories holding the word lists.
+/// * `installed` - Loads the installed word list.
+fn load_dictionary_with(
+    lang: &str,
+    data_dirs: &DataDirs,
+    installed: impl FnOnce() -> Result<Dictionary, Error>,
+) -> Result<Dictionary, Error> {
+    let mut dictionary = if let Some(dic) = hunspell_dictionary(lang, data_dirs) {
+        hunspell::load(&dic)?
+    } else {
+        if !SUPPORTED_LANGS.contains_key(lang) {
+            let variants = data_dirs.hunspell_variants(lang);
+            if variants.len() > 1 {
+                return Err(Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!(
+                        "There are Hunspell dictionaries for several variants of {}: {}. Choose one with `dym config set hunspell '{{{} = \"{}\"}}'`",
+                        LOCALES.get(lang).unwrap(),
+                        variants.join(", "),
+                        lang,
+                        variants[0]
+                    ),
+                ));
+            }
+        }
+        validate_lang(lang);
 
-        // Only delete and download if the language is supported.
-        if SUPPORTED_LANGS.contains_key(string) {
-            remove_file(data.join(&string)).expect("Failed to update file (deletion failed)");
-            fetch_word_list(string.to_string());
+        // Word lists embedded in the binary are used unless one is installed, and never
+        // downloaded.
+        match embedded::word_list(lang).filter(|_| data_dirs.find(lang).is_none()) {
+            Some(word_list) => Dictionary::parse(&word_list),
+            None => installed()?,
         }
+    };
+
+    // Add the words the user has added to their personal word list.
+    if let Some(personal) = data_dirs.personal(lang).filter(|path| path.is_file()) {
+        dictionary.extend_from_list(&read_to_string(personal)?);
     }
+
+    Ok(dictionary)
+}
+
+/// Load the installed word list for `lang`, downloading it first if it isn't installed and
+/// downloading it again if it is corrupt.
+///
+/// # Arguments
+///
+/// * `lang` - The locale code of the word list.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+fn load_installed(lang: &str, config: &Config, data_dirs: &DataDirs) -> Result<Dictionary, Error> {
+    let downloader = Downloader::new(config);
+    downloader.fetch_word_list(lang, data_dirs)?;
+
+    // Get word list. The program will only get here if/when this is a valid word list.
+    let word_list_path = data_dirs
+        .find(lang)
+        .ok_or_else(|| Error::new(io::ErrorKind::NotFound, "Word list not found"))?;
+    let dictionary = match read_installed(&word_list_path) {
+        Ok(dictionary) => dictionary,
+        // Replace corrupt downloads. Preinstalled word lists are left alone.
+        Err(error)
+            if error.kind() == io::ErrorKind::InvalidData
+                && data_dirs
+                    .user()
+                    .is_some_and(|user| word_list_path.starts_with(user)) =>
+        {
+            eprintln!(
+                "{}",
+                format!(
+                    "The {} word list is corrupt ({}), downloading it again",
+                    LOCALES.get(lang).unwrap(),
+                    error
+                )
+                .yellow()
+            );
+            downloader.download_word_list(lang, &word_list_path, None)?;
+            Dictionary::load(&word_list_path)?
+        }
+        Err(error) => return Err(error),
+    };
+
+    Ok(dictionary)
+}
+
+/// Load the installed word list at `path`, checking it against its metadata sidecar first.
+///
+/// # Arguments
+///
+/// * `path` - The path of the word list.
+fn read_installed(path: &Path) -> Result<Dictionary, Error> {
+    store::verify(path)?;
+    Dictionary::load(path)
 }
@@ -0,0 +1,106 @@
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use crate::config::Config;
+use crate::store::Metadata;
+use didyoumean::{data::DataDirs, langs::LOCALES};
+
+/// Location of the word list repository.
+pub const BASE_URL: &str = "https://raw.githubusercontent.com/hisbaan/wordlists/main";
+
+/// The result of downloading a word list. Nothing is ever downloaded without the `download`
+/// feature, so the variants only mirror the real downloader.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// A new version of the word list was downloaded.
+    Downloaded,
+    /// The source reported that the installed word list is up to date.
+    Unchanged,
+}
+
+/// Stands in for the downloader when dym is built without the `download` feature. Word lists that
+/// aren't installed can't be fetched and result in an `Unsupported` error explaining how to
+/// install them instead.
+#[derive(Debug, Clone)]
+pub struct Downloader;
+
+impl Downloader {
+    /// Create a downloader. The configuration is ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - The configuration.
+    pub fn new(_config: &Config) -> Downloader {
+        Downloader
+    }
+
+    /// Return an error if the word list specified by `lang` isn't installed.
+    ///
+    /// # Arguments
+    ///
+    /// * `lang` - A locale code string to define the word list file to fetch.
+    /// * `data_dirs` - The directories to search for an installed copy.
+    pub fn fetch_word_list(&self, lang: &str, data_dirs: &DataDirs) -> Result<(), Error> {
+        self.fetch_word_lists(&[lang.to_owned()], data_dirs)
+    }
+
+    /// Return an error if any of the word lists specified by `langs` isn't installed.
+    ///
+    /// # Arguments
+    ///
+    /// * `langs` - Locale code strings to define the word list files to fetch.
+    /// * `data_dirs` - The directories to search for installed copies.
+    pub fn fetch_word_lists(&self, langs: &[String], data_dirs: &DataDirs) -> Result<(), Error> {
+        match langs.iter().find(|lang| data_dirs.find(lang).is_none()) {
+            Some(lang) => Err(unsupported(&format!(
+                "The {} word list is not installed",
+                LOCALES.get(lang).map_or(lang.as_str(), |name| name)
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Return an error, as word lists can't be downloaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `lang` - A locale code string to define the word list file to fetch.
+    /// * `file_path` - The path to write the word list to.
+    /// * `previous` - The metadata of the installed word list.
+    pub fn download_word_list(
+        &self,
+        lang: &str,
+        _file_path: &Path,
+        _previous: Option<&Metadata>,
+    ) -> Result<Outcome, Error> {
+        Err(unsupported(&format!(
+            "The {} word list can't be downloaded",
+            LOCALES.get(lang).map_or(lang, |name| name)
+        )))
+    }
+
+    /// Return an error, as word lists can't be updated.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_dirs` - The directories holding the word lists.
+    pub fn update_langs(&self, _data_dirs: &DataDirs) -> Result<i32, Error> {
+        Err(unsupported("Word lists can't be updated"))
+    }
+}
+
+/// Return an `Unsupported` error starting with `message` that explains how to install word lists
+/// without downloading them.
+///
+/// # Arguments
+///
+/// * `message` - What couldn't be done.
+fn unsupported(message: &str) -> Error {
+    Error::new(
+        ErrorKind::Unsupported,
+        format!(
+            "{} and dym was built without the download feature. Install word lists with `dym lang import`, copy them into the data directory, or rebuild dym with the download feature",
+            message
+        ),
+    )
+}
@@ -0,0 +1,231 @@
+use colored::*;
+use dialoguer::{theme::ColorfulTheme, BasicHistory, Input};
+use std::{
+    collections::HashMap,
+    io::{self, BufRead, Error, ErrorKind},
+};
+
+use crate::{
+    config::Config, format_suggestions, hunspell_dictionary, load_dictionary, load_personal,
+    write_heading,
+};
+use didyoumean::{
+    data::DataDirs,
+    dictionary::Dictionary,
+    langs::{self, SUPPORTED_LANGS},
+};
+
+/// The number of lines kept in the history of a session.
+const HISTORY_SIZE: usize = 500;
+
+/// The commands understood by the REPL, printed by `:help`.
+const HELP: &str = "\
+Type a word to print suggestions for it, or one of these commands:
+  :lang [LANG]      Print or switch the language
+  :n [N]            Print or change the number of suggestions
+  :add WORD...      Add words to the personal word list of the language
+  :help             Print this message
+  :quit             Leave the session";
+
+/// A REPL session, holding the dictionaries of the languages used so far.
+struct Session<'a> {
+    config: &'a Config,
+    data_dirs: &'a DataDirs,
+    lang: String,
+    number: usize,
+    clean_output: bool,
+    verbose: bool,
+    dictionaries: HashMap<String, Dictionary>,
+}
+
+/// Whether the session goes on after a line.
+enum Flow {
+    Continue,
+    Quit,
+}
+
+/// Read words and commands line by line, printing suggestions for each word. Word lists are only
+/// loaded once per session. When standard input is a terminal, lines are edited with a history;
+/// otherwise they are read from standard input until it ends.
+///
+/// # Arguments
+///
+/// * `lang` - The locale code of the initial language.
+/// * `number` - The initial number of suggestions.
+/// * `clean_output` - Whether to print suggestions without the title, numbers or colour.
+/// * `verbose` - Whether to print the edit distance of each suggestion.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+pub fn run(
+    lang: &str,
+    number: usize,
+    clean_output: bool,
+    verbose: bool,
+    config: &Config,
+    data_dirs: &DataDirs,
+) -> Result<i32, Error> {
+    let mut session = Session {
+        config,
+        data_dirs,
+        lang: lang.to_owned(),
+        number,
+        clean_output,
+        verbose,
+        dictionaries: HashMap::new(),
+    };
+    session.switch_lang(lang)?;
+
+    if !atty::is(atty::Stream::Stdin) {
+        for line in io::stdin().lock().lines() {
+            if let Flow::Quit = session.handle(&line?)? {
+                break;
+            }
+        }
+        return Ok(0);
+    }
+
+    println!("{}", "Type :help for a list of commands".dimmed());
+    let theme = ColorfulTheme::default();
+    let mut history = BasicHistory::new()
+        .max_entries(HISTORY_SIZE)
+        .no_duplicates(true);
+    loop {
+        let line = Input::<String>::with_theme(&theme)
+            .with_prompt(&session.lang)
+            .allow_empty(true)
+            .report(false)
+            .history_with(&mut history)
+            .interact_text();
+        let line = match line {
+            Ok(line) => line,
+            // Ctrl+C and Ctrl+D end the session.
+            Err(dialoguer::Error::IO(error))
+                if matches!(
+                    error.kind(),
+                    ErrorKind::Interrupted | ErrorKind::UnexpectedEof
+                ) =>
+            {
+                break
+            }
+            Err(dialoguer::Error::IO(error)) => return Err(error),
+        };
+
+        if let Flow::Quit = session.handle(&line)? {
+            break;
+        }
+    }
+
+    Ok(0)
+}
+
+impl Session<'_> {
+    /// Run a command or print the suggestions for a word. Mistakes in commands are reported
+    /// without ending the session.
+    ///
+    /// # Arguments
+    ///
+    /// * `line` - A line read from the user.
+    fn handle(&mut self, line: &str) -> Result<Flow, Error> {
+        let line = line.trim();
+        let Some(command) = line.strip_prefix(':') else {
+            if !line.is_empty() {
+                self.suggest(line)?;
+            }
+            return Ok(Flow::Continue);
+        };
+
+        let mut words = command.split_whitespace();
+        match (words.next().unwrap_or_default(), words.next()) {
+            ("lang" | "l", None) => println!("{}", self.lang),
+            ("lang" | "l", Some(lang)) => {
+                if !SUPPORTED_LANGS.contains_key(lang)
+                    && hunspell_dictionary(lang, self.data_dirs).is_none()
+                {
+                    report(&format!("There is no word list for {}", lang));
+                } else if let Err(error) = self.switch_lang(lang) {
+                    report(&format!(
+                        "The {} word list can't be loaded: {}",
+                        lang, error
+                    ));
+                }
+            }
+            ("n", None) => println!("{}", self.number),
+            ("n", Some(number)) => match number.parse() {
+                Ok(number) if number > 0 => self.number = number,
+                _ => report(&format!("{} is not a positive number", number)),
+            },
+            ("add" | "a", Some(word)) => self.add(std::iter::once(word).chain(words).collect())?,
+            ("add" | "a", None) => report("Give the words to add"),
+            ("help" | "h" | "?", _) => println!("{}", HELP),
+            ("quit" | "q" | "exit", _) => return Ok(Flow::Quit),
+            (command, _) => report(&format!(
+                "Unknown command :{}, type :help for a list of commands",
+                command
+            )),
+        }
+
+        Ok(Flow::Continue)
+    }
+
+    /// Print the suggestions for `word` in the same format as `dym suggest`.
+    ///
+    /// # Arguments
+    ///
+    /// * `word` - The word to print suggestions for.
+    fn suggest(&self, word: &str) -> Result<(), Error> {
+        let suggestions = self.dictionaries[&self.lang].suggest(word, self.number);
+
+        let mut out = io::stdout();
+        write_heading(&mut out, word, &suggestions, self.clean_output)?;
+        for item in format_suggestions(&suggestions, self.number, self.clean_output, self.verbose) {
+            println!("{}", item);
+        }
+        Ok(())
+    }
+
+    /// Switch to `lang`, loading its dictionary unless it was loaded before.
+    ///
+    /// # Arguments
+    ///
+    /// * `lang` - The locale code of the language.
+    fn switch_lang(&mut self, lang: &str) -> Result<(), Error> {
+        if !self.dictionaries.contains_key(lang) {
+            let dictionary = load_dictionary(lang, self.config, self.data_dirs)?;
+            self.dictionaries.insert(lang.to_owned(), dictionary);
+        }
+        self.lang = lang.to_owned();
+        Ok(())
+    }
+
+    /// Add `words` to the personal word list of the current language, and to its dictionary so
+    /// they are known right away.
+    ///
+    /// # Arguments
+    ///
+    /// * `words` - The words to add.
+    fn add(&mut self, words: Vec<&str>) -> Result<(), Error> {
+        let (path, mut personal) = load_personal(&self.lang, self.data_dirs)?;
+        let before = personal.len();
+        personal.extend(words.iter().copied());
+        personal.save(&path)?;
+
+        if let Some(dictionary) = self.dictionaries.get_mut(&self.lang) {
+            dictionary.extend(words);
+        }
+        println!(
+            "Added {} words to the personal {} word list",
+            personal.len() - before,
+            langs::name(&self.lang).unwrap().blue()
+        );
+        Ok(())
+    }
+}
+
+/// Print a mistake made by the user.
+///
+/// # Arguments
+///
+/// * `message` - The message to print.
+fn report(message: &str) {
+    println!("{}", message.red());
+}
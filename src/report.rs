@@ -0,0 +1,269 @@
+use colored::*;
+use percent_encoding::{utf8_percent_encode, AsciiSet, CONTROLS};
+use serde_json::{json, Value};
+use std::io::{Error, Write};
+use url::Url;
+
+use crate::check::Misspelling;
+
+mod format;
+pub use format::Format;
+
+/// The id of the rule reported by Checkstyle and SARIF output.
+const RULE: &str = "misspelling";
+
+/// The name given to standard input in diagnostics.
+pub const STDIN: &str = "<stdin>";
+
+/// The characters percent-encoded in the path of a relative URI: those that aren't allowed in a
+/// path, and `%` so it isn't read as an escape.
+const PATH: &AsciiSet = &CONTROLS
+    .add(b' ')
+    .add(b'"')
+    .add(b'#')
+    .add(b'%')
+    .add(b'<')
+    .add(b'>')
+    .add(b'?')
+    .add(b'[')
+    .add(b']')
+    .add(b'^')
+    .add(b'`')
+    .add(b'{')
+    .add(b'|')
+    .add(b'}');
+
+/// A misspelled word in a checked file, the common form of every output format.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The name of the file, or `STDIN`.
+    pub file: String,
+    /// The 1-based line of the word.
+    pub line: usize,
+    /// The 1-based column of the word, counted in characters.
+    pub column: usize,
+    pub word: String,
+    pub suggestions: Vec<String>,
+}
+
+impl Diagnostic {
+    /// Create a diagnostic for a misspelling found in `file`.
+    ///
+    /// # Arguments
+    ///
+    /// * `file` - The name of the checked file.
+    /// * `misspelling` - The misspelled word.
+    pub fn new(file: &str, misspelling: Misspelling) -> Diagnostic {
+        Diagnostic {
+            file: file.to_owned(),
+            line: misspelling.line,
+            column: misspelling.column,
+            word: misspelling.word,
+            suggestions: misspelling.suggestions,
+        }
+    }
+
+    /// Return the column just after the word.
+    pub fn end_column(&self) -> usize {
+        self.column + self.word.chars().count()
+    }
+
+    /// Return a sentence describing the misspelling.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use didyoumean::{check::check, dictionary::Dictionary, report::Diagnostic};
+    /// let dictionary = Dictionary::parse("world\nword");
+    /// let misspelling = check("wrld", &dictionary, 2).remove(0);
+    /// let diagnostic = Diagnostic::new("notes.txt", misspelling);
+    ///
+    /// assert_eq!(diagnostic.message(), "Unknown word \"wrld\", did you mean world, word?");
+    /// ```
+    pub fn message(&self) -> String {
+        if self.suggestions.is_empty() {
+            format!("Unknown word \"{}\"", self.word)
+        } else {
+            format!(
+                "Unknown word \"{}\", did you mean {}?",
+                self.word,
+                self.suggestions.join(", ")
+            )
+        }
+    }
+}
+
+/// Write `diagnostics` to `out` in `format`. Quickfix output is coloured unless colours are
+/// turned off.
+///
+/// # Arguments
+///
+/// * `out` - Where to write the diagnostics.
+/// * `format` - The output format.
+/// * `diagnostics` - The diagnostics, grouped by file.
+///
+/// # Examples
+///
+/// ```
+/// # use didyoumean::report::{write, Diagnostic, Format};
+/// colored::control::set_override(false);
+/// let diagnostic = Diagnostic {
+///     file: "notes.txt".to_owned(),
+///     line: 2,
+///     column: 5,
+///     word: "wrld".to_owned(),
+///     suggestions: vec!["world".to_owned()],
+/// };
+/// let mut out = vec![];
+/// write(&mut out, Format::Quickfix, &[diagnostic]).unwrap();
+///
+/// assert_eq!(String::from_utf8(out).unwrap(), "notes.txt:2:5: wrld -> world\n");
+/// ```
+pub fn write(out: &mut dyn Write, format: Format, diagnostics: &[Diagnostic]) -> Result<(), Error> {
+    match format {
+        Format::Quickfix => write_quickfix(out, diagnostics),
+        Format::Checkstyle => write_checkstyle(out, diagnostics),
+        Format::Sarif => write_sarif(out, diagnostics),
+    }
+}
+
+/// Write `diagnostics` as `file:line:column: word -> suggestions` lines.
+///
+/// # Arguments
+///
+/// * `out` - Where to write the diagnostics.
+/// * `diagnostics` - The diagnostics to write.
+fn write_quickfix(out: &mut dyn Write, diagnostics: &[Diagnostic]) -> Result<(), Error> {
+    for diagnostic in diagnostics {
+        writeln!(
+            out,
+            "{} {} -> {}",
+            format!(
+                "{}:{}:{}:",
+                diagnostic.file, diagnostic.line, diagnostic.column
+            )
+            .bold(),
+            diagnostic.word.red(),
+            diagnostic.suggestions.join(", ").green()
+        )?;
+    }
+    Ok(())
+}
+
+/// Write `diagnostics` as a Checkstyle report, with a `file` element for each run of diagnostics
+/// in the same file.
+///
+/// # Arguments
+///
+/// * `out` - Where to write the diagnostics.
+/// * `diagnostics` - The diagnostics to write, grouped by file.
+fn write_checkstyle(out: &mut dyn Write, diagnostics: &[Diagnostic]) -> Result<(), Error> {
+    writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+    writeln!(out, "<checkstyle version=\"4.3\">")?;
+    for group in diagnostics.chunk_by(|a, b| a.file == b.file) {
+        writeln!(out, "  <file name=\"{}\">", escape_xml(&group[0].file))?;
+        for diagnostic in group {
+            writeln!(
+                out,
+                "    <error line=\"{}\" column=\"{}\" severity=\"warning\" message=\"{}\" source=\"didyoumean.{}\"/>",
+                diagnostic.line,
+                diagnostic.column,
+                escape_xml(&diagnostic.message()),
+                RULE
+            )?;
+        }
+        writeln!(out, "  </file>")?;
+    }
+    writeln!(out, "</checkstyle>")
+}
+
+/// Write `diagnostics` as a SARIF log with a single run. Columns are counted in Unicode code
+/// points, as they are everywhere else.
+///
+/// # Arguments
+///
+/// * `out` - Where to write the diagnostics.
+/// * `diagnostics` - The diagnostics to write.
+fn write_sarif(out: &mut dyn Write, diagnostics: &[Diagnostic]) -> Result<(), Error> {
+    let results: Vec<_> = diagnostics
+        .iter()
+        .map(|diagnostic| {
+            json!({
+                "ruleId": RULE,
+                "level": "warning",
+                "message": {"text": diagnostic.message()},
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": artifact_location(&diagnostic.file),
+                        "region": {
+                            "startLine": diagnostic.line,
+                            "startColumn": diagnostic.column,
+                            "endColumn": diagnostic.end_column(),
+                        },
+                    },
+                }],
+            })
+        })
+        .collect();
+
+    let log = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "dym",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": "https://github.com/hisbaan/didyoumean",
+                    "rules": [{
+                        "id": RULE,
+                        "shortDescription": {"text": "Word not in the word list"},
+                    }],
+                },
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    });
+    serde_json::to_writer_pretty(&mut *out, &log)?;
+    writeln!(out)
+}
+
+/// Return the location of `file` in a SARIF log. Relative paths are percent-encoded relative
+/// references, which SARIF viewers resolve against the repository, while absolute paths become
+/// `file` URIs. Standard input has no URI and is only described.
+///
+/// # Arguments
+///
+/// * `file` - The name of the checked file.
+fn artifact_location(file: &str) -> Value {
+    if file == STDIN {
+        return json!({"description": {"text": "Standard input"}});
+    }
+
+    let uri = match Url::from_file_path(file) {
+        Ok(url) => url.to_string(),
+        Err(()) => utf8_percent_encode(&file.replace('\\', "/"), PATH).to_string(),
+    };
+    json!({"uri": uri})
+}
+
+/// Escape the characters of `text` that are special in XML attribute values.
+///
+/// # Arguments
+///
+/// * `text` - The text to escape.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,12 @@
+/// How diagnostics are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Format {
+    /// `file:line:column: word -> suggestions`, one per line, as read by Vim's quickfix list and
+    /// tools parsing GCC's messages.
+    #[value(alias = "gcc")]
+    Quickfix,
+    /// Checkstyle XML.
+    Checkstyle,
+    /// SARIF 2.1.0 JSON.
+    Sarif,
+}
@@ -0,0 +1,528 @@
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Error, ErrorKind, Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::Arc,
+    time::Duration,
+};
+
+use crate::{
+    cache::Dictionaries,
+    config::{Config, MAX_NUMBER},
+    workers,
+};
+use didyoumean::{check::check, data::DataDirs};
+
+/// The largest request body accepted, in bytes.
+const MAX_BODY_SIZE: u64 = 1024 * 1024;
+
+/// The largest request line and headers accepted, in bytes.
+const MAX_HEAD_SIZE: u64 = 16 * 1024;
+
+/// How long a client may take to send its request or read the response.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long browsers may cache the answer to a CORS preflight request, in seconds.
+const CORS_MAX_AGE: u32 = 86400;
+
+/// The number of suggestions for each misspelled word when `POST /check` isn't given `n`, as for
+/// `dym check`.
+const DEFAULT_CHECK_NUMBER: usize = 3;
+
+/// An HTTP request, with its query string decoded.
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    content_type: String,
+    /// The `Origin` header, sent by browsers on cross-origin requests.
+    origin: Option<String>,
+    body: Vec<u8>,
+}
+
+/// An HTTP response with a JSON body.
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    /// Create a response with `body` serialized as JSON.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The HTTP status code.
+    /// * `body` - The value to send as JSON.
+    fn json<T: Serialize>(status: u16, body: &T) -> Response {
+        Response {
+            status,
+            body: serde_json::to_string(body).unwrap(),
+        }
+    }
+
+    /// Create an error response explaining what went wrong.
+    ///
+    /// # Arguments
+    ///
+    /// * `status` - The HTTP status code.
+    /// * `message` - The reason for the error.
+    fn error(status: u16, message: impl Into<String>) -> Response {
+        Response::json(
+            status,
+            &ErrorBody {
+                error: message.into(),
+            },
+        )
+    }
+
+    /// Create an empty response, as sent to CORS preflight requests.
+    fn empty() -> Response {
+        Response {
+            status: 204,
+            body: String::new(),
+        }
+    }
+}
+
+/// The body of `GET /health`.
+#[derive(Serialize)]
+struct HealthBody {
+    status: &'static str,
+    version: &'static str,
+}
+
+/// A suggested word and its edit distance to the queried word.
+#[derive(Serialize)]
+struct SuggestionBody {
+    word: String,
+    distance: usize,
+}
+
+/// The body of `GET /suggest`.
+#[derive(Serialize)]
+struct SuggestBody {
+    lang: String,
+    suggestions: Vec<SuggestionBody>,
+}
+
+/// A misspelled word, where it was found, and its closest known words.
+#[derive(Serialize)]
+struct MisspellingBody {
+    word: String,
+    line: usize,
+    column: usize,
+    suggestions: Vec<String>,
+}
+
+/// The body of `POST /check`.
+#[derive(Serialize)]
+struct CheckBody {
+    lang: String,
+    misspellings: Vec<MisspellingBody>,
+}
+
+/// The body of a failed request.
+#[derive(Serialize)]
+struct ErrorBody {
+    error: String,
+}
+
+/// A JSON request body for `POST /check`. The query string parameters are used for missing
+/// fields.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CheckRequest {
+    text: String,
+    lang: Option<String>,
+    n: Option<usize>,
+}
+
+/// Serve suggestions over HTTP on `bind` until the process is killed, loading the word lists of
+/// `langs` up front. Connections are answered by a fixed number of threads, and web pages from the
+/// origins in the `cors_origins` configuration key may call the server.
+///
+/// # Arguments
+///
+/// * `bind` - The address and port to listen on.
+/// * `langs` - The locale codes of the word lists to load right away.
+/// * `config` - The configuration file.
+/// * `data_dirs` - The directories holding the word lists.
+pub fn serve(
+    bind: &str,
+    langs: &[String],
+    config: Config,
+    data_dirs: DataDirs,
+) -> Result<i32, Error> {
+    let dictionaries = Arc::new(Dictionaries::new(config, data_dirs));
+    for lang in langs {
+        dictionaries.get(lang)?;
+    }
+
+    let listener = TcpListener::bind(bind)?;
+    println!(
+        "{}",
+        format!("Listening on http://{}", listener.local_addr()?)
+            .green()
+            .bold()
+    );
+
+    workers::serve(listener.incoming(), move |stream| {
+        handle(stream, &dictionaries)
+    })?;
+
+    Ok(0)
+}
+
+/// Answer the request sent on `stream` and close the connection.
+///
+/// # Arguments
+///
+/// * `stream` - The connection to the client.
+/// * `dictionaries` - The dictionaries to answer with.
+fn handle(mut stream: TcpStream, dictionaries: &Dictionaries) {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).ok();
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT)).ok();
+
+    let (response, cors) = match read_request(&stream) {
+        Ok(request) => (
+            route(&request, dictionaries),
+            cors_headers(&request, dictionaries.config()),
+        ),
+        Err(response) => (response, String::new()),
+    };
+
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n",
+        response.status,
+        reason(response.status),
+        response.body.len(),
+        cors
+    );
+    stream.write_all(head.as_bytes()).ok();
+    stream.write_all(response.body.as_bytes()).ok();
+}
+
+/// Read an HTTP/1.1 request from `stream`. Return the response to send instead if the request is
+/// invalid or too large.
+///
+/// # Arguments
+///
+/// * `stream` - The connection to the client.
+fn read_request(stream: &TcpStream) -> Result<Request, Response> {
+    let bad_request = |message: &str| Response::error(400, message);
+    let mut reader = BufReader::new(stream.take(MAX_HEAD_SIZE));
+
+    let mut line = String::new();
+    reader
+        .read_line(&mut line)
+        .map_err(|_| bad_request("The request line can't be read"))?;
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return Err(bad_request("The request line is invalid"));
+    };
+    let method = method.to_owned();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let path = path.to_owned();
+    let query = parse_query(query).ok_or_else(|| bad_request("The query string is invalid"))?;
+
+    // Read the headers, only keeping the ones describing the body and its origin.
+    let mut content_length = 0;
+    let mut content_type = String::new();
+    let mut origin = None;
+    loop {
+        line.clear();
+        match reader.read_line(&mut line) {
+            Ok(0) => return Err(Response::error(431, "The request headers are too large")),
+            Ok(_) => {}
+            Err(_) => return Err(bad_request("The request headers can't be read")),
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(bad_request("A request header is invalid"));
+        };
+        let value = value.trim();
+        match name.to_ascii_lowercase().as_str() {
+            "content-length" => {
+                content_length = value
+                    .parse()
+                    .map_err(|_| bad_request("Content-Length is invalid"))?;
+            }
+            "content-type" => content_type = value.to_ascii_lowercase(),
+            "origin" => origin = Some(value.to_owned()),
+            "transfer-encoding" => {
+                return Err(Response::error(411, "Send the body with a Content-Length"))
+            }
+            _ => {}
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return Err(Response::error(
+            413,
+            format!("The body is larger than {} bytes", MAX_BODY_SIZE),
+        ));
+    }
+
+    // The body follows the headers, part of which may already be buffered.
+    let buffered = reader.buffer().to_vec();
+    let mut body = buffered;
+    body.truncate(content_length as usize);
+    let remaining = content_length - body.len() as u64;
+    stream
+        .take(remaining)
+        .read_to_end(&mut body)
+        .map_err(|_| bad_request("The body can't be read"))?;
+    if (body.len() as u64) < content_length {
+        return Err(bad_request("The body is shorter than its Content-Length"));
+    }
+
+    Ok(Request {
+        method,
+        path,
+        query,
+        content_type,
+        origin,
+        body,
+    })
+}
+
+/// Return the CORS headers of the response to `request`, each ending with a line break. Requests
+/// from origins that aren't allowed get none, so browsers don't let the page read the response.
+///
+/// # Arguments
+///
+/// * `request` - The request of the client.
+/// * `config` - The configuration file, listing the allowed origins.
+fn cors_headers(request: &Request, config: &Config) -> String {
+    let allowed = config.cors_origins.as_deref().unwrap_or_default();
+    let Some(origin) = &request.origin else {
+        return String::new();
+    };
+    let allow_origin = if allowed.iter().any(|allowed| allowed == "*") {
+        "*"
+    } else if allowed.iter().any(|allowed| allowed == origin) {
+        origin
+    } else {
+        return String::new();
+    };
+
+    let mut headers = format!(
+        "Access-Control-Allow-Origin: {}\r\nVary: Origin\r\n",
+        allow_origin
+    );
+    if request.method == "OPTIONS" {
+        headers.push_str(&format!(
+            "Access-Control-Allow-Methods: GET, POST, OPTIONS\r\nAccess-Control-Allow-Headers: Content-Type\r\nAccess-Control-Max-Age: {}\r\n",
+            CORS_MAX_AGE
+        ));
+    }
+    headers
+}
+
+/// Return the response to `request`.
+///
+/// # Arguments
+///
+/// * `request` - The request of the client.
+/// * `dictionaries` - The dictionaries to answer with.
+fn route(request: &Request, dictionaries: &Dictionaries) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/health") => Response::json(
+            200,
+            &HealthBody {
+                status: "ok",
+                version: env!("CARGO_PKG_VERSION"),
+            },
+        ),
+        ("GET", "/suggest") => suggest(request, dictionaries).unwrap_or_else(|error| error),
+        ("POST", "/check") => check_text(request, dictionaries).unwrap_or_else(|error| error),
+        ("OPTIONS", "/health" | "/suggest" | "/check") => Response::empty(),
+        (_, "/health" | "/suggest" | "/check") => Response::error(
+            405,
+            format!("{} is not allowed on {}", request.method, request.path),
+        ),
+        _ => Response::error(404, format!("{} was not found", request.path)),
+    }
+}
+
+/// Answer `GET /suggest?q=WORD&lang=LANG&n=N` with the closest words to `q`.
+///
+/// # Arguments
+///
+/// * `request` - The request of the client.
+/// * `dictionaries` - The dictionaries to answer with.
+fn suggest(request: &Request, dictionaries: &Dictionaries) -> Result<Response, Response> {
+    let word = request
+        .query
+        .get("q")
+        .map(|word| word.trim())
+        .filter(|word| !word.is_empty())
+        .ok_or_else(|| Response::error(400, "The q parameter is missing"))?;
+    let lang = requested_lang(request.query.get("lang").cloned(), dictionaries);
+    let number = requested_number(&request.query)?.unwrap_or(dictionaries.config().number());
+
+    let dictionary = dictionaries.get(&lang).map_err(error_response)?;
+    let suggestions = dictionary
+        .suggest(word, number)
+        .into_iter()
+        .map(|suggestion| SuggestionBody {
+            word: suggestion.word.to_owned(),
+            distance: suggestion.distance,
+        })
+        .collect();
+
+    Ok(Response::json(200, &SuggestBody { lang, suggestions }))
+}
+
+/// Answer `POST /check?lang=LANG&n=N` with the misspelled words in the body. The body is either
+/// plain text or, with a JSON content type, an object with a `text` field and optional `lang`
+/// and `n` fields.
+///
+/// # Arguments
+///
+/// * `request` - The request of the client.
+/// * `dictionaries` - The dictionaries to answer with.
+fn check_text(request: &Request, dictionaries: &Dictionaries) -> Result<Response, Response> {
+    let mut lang = request.query.get("lang").cloned();
+    let mut number = requested_number(&request.query)?;
+    let text = if request.content_type.starts_with("application/json") {
+        let body: CheckRequest = serde_json::from_slice(&request.body)
+            .map_err(|error| Response::error(400, format!("The body is invalid: {}", error)))?;
+        lang = body.lang.or(lang);
+        number = body.n.or(number);
+        body.text
+    } else {
+        String::from_utf8(request.body.clone())
+            .map_err(|_| Response::error(400, "The body is not valid UTF-8"))?
+    };
+    let lang = requested_lang(lang, dictionaries);
+
+    let dictionary = dictionaries.get(&lang).map_err(error_response)?;
+    let misspellings = check(&text, &dictionary, number.unwrap_or(DEFAULT_CHECK_NUMBER))
+        .into_iter()
+        .map(|misspelling| MisspellingBody {
+            word: misspelling.word,
+            line: misspelling.line,
+            column: misspelling.column,
+            suggestions: misspelling.suggestions,
+        })
+        .collect();
+
+    Ok(Response::json(200, &CheckBody { lang, misspellings }))
+}
+
+/// Return the requested language, or the configured one.
+///
+/// # Arguments
+///
+/// * `lang` - The locale code given in the request.
+/// * `dictionaries` - The dictionaries to answer with.
+fn requested_lang(lang: Option<String>, dictionaries: &Dictionaries) -> String {
+    lang.filter(|lang| !lang.is_empty())
+        .unwrap_or_else(|| dictionaries.config().lang().to_owned())
+}
+
+/// Return the `n` query string parameter, or an error response if it isn't a positive number
+/// no larger than `MAX_NUMBER`.
+///
+/// # Arguments
+///
+/// * `query` - The query string parameters.
+fn requested_number(query: &HashMap<String, String>) -> Result<Option<usize>, Response> {
+    match query.get("n").map(|n| n.parse::<usize>()) {
+        None => Ok(None),
+        Some(Ok(n)) if n > MAX_NUMBER => Err(Response::error(
+            400,
+            format!("The n parameter can't be larger than {}", MAX_NUMBER),
+        )),
+        Some(Ok(n)) if n > 0 => Ok(Some(n)),
+        Some(_) => Err(Response::error(
+            400,
+            "The n parameter is not a positive number",
+        )),
+    }
+}
+
+/// Return the response for a dictionary that can't be loaded.
+///
+/// # Arguments
+///
+/// * `error` - The reason the dictionary can't be loaded.
+fn error_response(error: Error) -> Response {
+    let status = match error.kind() {
+        ErrorKind::InvalidInput => 400,
+        ErrorKind::NotFound => 404,
+        _ => 500,
+    };
+    Response::error(status, error.to_string())
+}
+
+/// Decode a URL query string into its parameters. Return `None` if it isn't valid.
+///
+/// # Arguments
+///
+/// * `query` - The query string, without the leading `?`.
+fn parse_query(query: &str) -> Option<HashMap<String, String>> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            Some((decode(name)?, decode(value)?))
+        })
+        .collect()
+}
+
+/// Decode a percent-encoded query string component, where `+` stands for a space. Return `None`
+/// if it isn't valid.
+///
+/// # Arguments
+///
+/// * `component` - The component to decode.
+fn decode(component: &str) -> Option<String> {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => {
+                // `from_str_radix` alone would also take a sign, as in `%+1`.
+                let hex = bytes.get(i + 1..i + 3)?;
+                if !hex.iter().all(u8::is_ascii_hexdigit) {
+                    return None;
+                }
+                let hex = std::str::from_utf8(hex).ok()?;
+                decoded.push(u8::from_str_radix(hex, 16).ok()?);
+                i += 2;
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8(decoded).ok()
+}
+
+/// Return the reason phrase of an HTTP status code.
+///
+/// # Arguments
+///
+/// * `status` - The HTTP status code.
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        411 => "Length Required",
+        413 => "Payload Too Large",
+        431 => "Request Header Fields Too Large",
+        _ => "Internal Server Error",
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{read_to_string, remove_file, rename, write},
+    io::{Error, ErrorKind},
+    path::{Path, PathBuf},
+};
+
+/// Information about a downloaded word list, stored next to it in `<lang>.meta`.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Metadata {
+    /// Size of the word list file in bytes, which is compressed if the word list is stored
+    /// compressed.
+    pub size: u64,
+    /// Hex encoded SHA-256 checksum of the uncompressed word list.
+    pub sha256: String,
+    /// Base URL the word list was downloaded from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// `ETag` header of the response the word list was downloaded from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// `Last-Modified` header of the response the word list was downloaded from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+/// Return the path of the metadata sidecar of the word list at `path`.
+///
+/// # Arguments
+///
+/// * `path` - The path of a word list.
+pub fn metadata_path(path: &Path) -> PathBuf {
+    let mut file_name = path.file_name().unwrap_or_default().to_owned();
+    file_name.push(".meta");
+    path.with_file_name(file_name)
+}
+
+/// Return the path a word list is downloaded to before it is moved to `path`.
+///
+/// # Arguments
+///
+/// * `path` - The final path of a word list.
+pub fn partial_path(path: &Path) -> PathBuf {
+    let mut file_name = std::ffi::OsString::from(".");
+    file_name.push(path.file_name().unwrap_or_default());
+    file_name.push(".part");
+    path.with_file_name(file_name)
+}
+
+/// Read the metadata sidecar of the word list at `path`, if there is a valid one.
+///
+/// # Arguments
+///
+/// * `path` - The path of a word list.
+pub fn read_metadata(path: &Path) -> Option<Metadata> {
+    toml::from_str(&read_to_string(metadata_path(path)).ok()?).ok()
+}
+
+/// Atomically write the metadata sidecar of the word list at `path`.
+///
+/// # Arguments
+///
+/// * `path` - The path of a word list.
+/// * `metadata` - The metadata to store.
+pub fn write_metadata(path: &Path, metadata: &Metadata) -> Result<(), Error> {
+    let sidecar = metadata_path(path);
+    let partial = partial_path(&sidecar);
+    write(&partial, toml::to_string(metadata).unwrap())?;
+    rename(partial, sidecar)
+}
+
+/// Check the word list at `path` against its metadata sidecar. Return an `InvalidData` error if
+/// its size doesn't match, which happens when a download was truncated or the file was modified.
+/// Word lists without a sidecar, such as preinstalled ones, are assumed to be valid.
+///
+/// # Arguments
+///
+/// * `path` - The path of a word list.
+pub fn verify(path: &Path) -> Result<(), Error> {
+    if let Some(metadata) = read_metadata(path) {
+        let size = path.metadata()?.len();
+        if size != metadata.size {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("expected {} bytes but found {} bytes", metadata.size, size),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Delete the word list at `path` along with its metadata sidecar.
+///
+/// # Arguments
+///
+/// * `path` - The path of a word list.
+pub fn remove(path: &Path) -> Result<(), Error> {
+    remove_file(path)?;
+
+    // Older versions didn't write a sidecar, so it may not exist.
+    match remove_file(metadata_path(path)) {
+        Err(error) if error.kind() != ErrorKind::NotFound => Err(error),
+        _ => Ok(()),
+    }
+}
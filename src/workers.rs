@@ -0,0 +1,50 @@
+use colored::*;
+use std::{
+    io::Error,
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+/// The number of connections answered at the same time by long-running commands. Further
+/// connections wait for one of them to finish.
+pub const WORKERS: usize = 16;
+
+/// Answer the connections of `incoming` with `handle` on `WORKERS` threads until the listener
+/// stops. Accepting blocks while every thread is busy and `WORKERS` more connections are queued,
+/// so a flood of clients can't exhaust the threads of the process.
+///
+/// # Arguments
+///
+/// * `incoming` - The connections accepted by a listener.
+/// * `handle` - Answers a connection.
+pub fn serve<S, H>(incoming: impl Iterator<Item = Result<S, Error>>, handle: H) -> Result<(), Error>
+where
+    S: Send + 'static,
+    H: Fn(S) + Send + Sync + 'static,
+{
+    let (sender, receiver) = mpsc::sync_channel::<S>(WORKERS);
+    let receiver = Arc::new(Mutex::new(receiver));
+    let handle = Arc::new(handle);
+    for _ in 0..WORKERS {
+        let receiver = Arc::clone(&receiver);
+        let handle = Arc::clone(&handle);
+        thread::spawn(move || loop {
+            let stream = match receiver.lock().unwrap().recv() {
+                Ok(stream) => stream,
+                Err(_) => return,
+            };
+            handle(stream);
+        });
+    }
+
+    for stream in incoming {
+        match stream {
+            Ok(stream) => sender
+                .send(stream)
+                .map_err(|_| Error::other("The connection workers stopped"))?,
+            Err(error) => eprintln!("{}", format!("Connection failed: {}", error).red()),
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use common::{data_dir, dym};
+use flate2::{write::GzEncoder, Compression};
+use std::fs::{read_to_string, remove_dir_all, write, File};
+use tar::{Builder, Header};
+
+#[test]
+fn export_import_test() {
+    let source = data_dir("export");
+    write(source.join("en"), "hello\nworld\n").unwrap();
+    write(source.join("fr"), "bonjour\n").unwrap();
+    let bundle = source.join("bundle.tar.gz");
+
+    let output = dym(&source, &["lang", "export", bundle.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "Exported English word list\nExported French word list\n"
+    );
+
+    let target = data_dir("import");
+    let output = dym(&target, &["lang", "import", bundle.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert_eq!(read_to_string(target.join("en")).unwrap(), "hello\nworld\n");
+    assert_eq!(read_to_string(target.join("fr")).unwrap(), "bonjour\n");
+    assert!(target.join("en.meta").is_file());
+
+    // The imported word lists are used without downloading anything.
+    let output = dym(&target, &["helo", "-c", "-n", "1"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+
+    remove_dir_all(&source).unwrap();
+    remove_dir_all(&target).unwrap();
+}
+
+#[test]
+fn import_invalid_bundle_test() {
+    let dir = data_dir("invalid");
+    let bundle = dir.join("bundle.tar.gz");
+
+    // Build a bundle whose word list doesn't match its checksum.
+    let manifest = "version = 1\n\n[word_lists.en]\nsize = 6\nsha256 = \"0000\"\n";
+    let mut builder = Builder::new(GzEncoder::new(
+        File::create(&bundle).unwrap(),
+        Compression::default(),
+    ));
+    for (name, contents) in [("bundle.toml", manifest.as_bytes()), ("en", b"hello\n")] {
+        let mut header = Header::new_gnu();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        builder.append_data(&mut header, name, contents).unwrap();
+    }
+    builder.into_inner().unwrap().finish().unwrap();
+
+    let output = dym(&dir, &["lang", "import", bundle.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("checksum"));
+    assert!(!dir.join("en").exists());
+
+    remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,366 @@
+mod common;
+
+use common::{command, dym, dym_with_input, word_list_dir};
+use std::{
+    fs::{create_dir_all, remove_dir_all, write},
+    process::Command,
+};
+
+/// The English word list of the tests.
+const WORDS: &str = "hello\nhelp\nworld\nword\nthe\n";
+
+#[test]
+fn suggest_shortcut_test() {
+    let dir = word_list_dir("suggest", WORDS);
+
+    let shortcut = dym(&dir, &["helo", "-c", "-n", "2"]);
+    let subcommand = dym(&dir, &["suggest", "helo", "-c", "-n", "2"]);
+
+    assert!(shortcut.status.success());
+    assert_eq!(String::from_utf8_lossy(&shortcut.stdout), "hello\nhelp\n");
+    assert_eq!(shortcut.stdout, subcommand.stdout);
+
+    // Short words aren't taken by subcommand aliases.
+    let output = dym(&dir, &["i", "-c", "-n", "1"]);
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "the
+"
+    );
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_and_dict_test() {
+    let dir = word_list_dir("check", WORDS);
+
+    let output = dym_with_input(&dir, &["check", "-n", "1"], "the wrld\n");
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "<stdin>:1:5: wrld -> world\n"
+    );
+
+    // Words in the personal word list are no longer reported.
+    assert!(dym(&dir, &["dict", "add", "wrld"]).status.success());
+    let output = dym_with_input(&dir, &["check"], "the wrld\n");
+    assert_eq!(output.status.code(), Some(0));
+    assert!(output.stdout.is_empty());
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn lang_list_and_remove_test() {
+    let dir = word_list_dir("lang", WORDS);
+
+    let output = dym(&dir, &["lang", "list", "--installed"]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains(" - en: English (26 B, modified "));
+    assert!(!stdout.contains(" - fr: "));
+
+    assert!(dym(&dir, &["lang", "remove", "en"]).status.success());
+    assert!(!dir.join("en").exists());
+
+    // Removing a language that isn't installed fails.
+    assert_eq!(dym(&dir, &["lang", "remove", "en"]).status.code(), Some(1));
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(not(feature = "download"))]
+#[test]
+fn missing_lang_without_download_test() {
+    let dir = word_list_dir("offline", WORDS);
+
+    let output = dym(&dir, &["helo", "-c", "--lang", "fr"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains(
+        "The French word list is not installed and dym was built without the download feature"
+    ));
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(not(feature = "clipboard"))]
+#[test]
+fn clipboard_config_without_clipboard_test() {
+    let dir = word_list_dir("clipboard-config", WORDS);
+    write(
+        dir.join("config.toml"),
+        "clipboard = \"xclip\"\nclipboard_selection = \"primary\"\nclipboard_lifetime = 5\n",
+    )
+    .unwrap();
+
+    // The clipboard keys are ignored rather than rejected as unknown.
+    let output = dym(&dir, &["helo", "-c"]);
+    assert_eq!(output.status.code(), Some(0));
+    let output = dym(&dir, &["config", "show"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("clipboard = \"xclip\""));
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn pick_and_output_test() {
+    let dir = word_list_dir("pick", WORDS);
+
+    // Only the picked word is printed.
+    let output = dym(&dir, &["helo", "--pick", "2"]);
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "help\n");
+
+    let file = dir.join("word");
+    let output = dym(
+        &dir,
+        &["helo", "--pick", "1", "--output", file.to_str().unwrap()],
+    );
+    assert!(output.status.success());
+    assert!(output.stdout.is_empty());
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "hello\n");
+
+    // The word is passed to the type command as its last argument, even if its path has spaces.
+    create_dir_all(dir.join("type commands")).unwrap();
+    let script = dir.join("type commands").join("type");
+    write(
+        &script,
+        format!("#!/bin/sh\necho \"$@\" > '{}'\n", file.display()),
+    )
+    .unwrap();
+    std::fs::set_permissions(&script, std::os::unix::fs::PermissionsExt::from_mode(0o755)).unwrap();
+    write(
+        dir.join("config.toml"),
+        format!("type_command = \"'{}' --delay 0\"\n", script.display()),
+    )
+    .unwrap();
+    let output = dym(&dir, &["helo", "--pick", "2", "--type"]);
+    assert!(output.status.success());
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "--delay 0 help\n");
+
+    write(
+        dir.join("config.toml"),
+        format!("type_command = ['{}', '--delay', '1']\n", script.display()),
+    )
+    .unwrap();
+    let output = dym(&dir, &["helo", "--pick", "1", "--type"]);
+    assert!(output.status.success());
+    assert_eq!(std::fs::read_to_string(&file).unwrap(), "--delay 1 hello\n");
+
+    // Without a terminal, the word can only be chosen with --pick.
+    let output = dym(&dir, &["helo", "--print"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("pass --pick"));
+
+    let output = dym(&dir, &["helo", "-n", "2", "--pick", "3"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("There is no suggestion 3"));
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn interactive_without_terminal_test() {
+    let dir = word_list_dir("interactive", WORDS);
+
+    let output = dym(&dir, &["interactive", "helo"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("needs a terminal"));
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn repl_test() {
+    let dir = word_list_dir("repl", WORDS);
+    write(dir.join("fr"), "bonjour\n").unwrap();
+
+    let output = dym_with_input(
+        &dir,
+        &["repl", "-c"],
+        "helo\n:n 1\nwrld\n:add wrld\nwrld\n:lang fr\nbonjur\n:nope\n:q\nhelo\n",
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "hello\nhelp\nthe\nworld\nword\nworld\n\
+         Added 1 words to the personal English word list\nwrld\nbonjour\n\
+         Unknown command :nope, type :help for a list of commands\n"
+    );
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(unix)]
+#[test]
+fn cmd_test() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = word_list_dir("cmd", WORDS);
+    let bin = dir.join("bin");
+    create_dir_all(&bin).unwrap();
+    for (name, mode) in [("git", 0o755), ("grep", 0o755), ("gitk", 0o644)] {
+        write(bin.join(name), "").unwrap();
+        std::fs::set_permissions(bin.join(name), std::fs::Permissions::from_mode(mode)).unwrap();
+    }
+
+    // Files that aren't executable and names too far from the typo aren't suggested.
+    let output = Command::new(env!("CARGO_BIN_EXE_dym"))
+        .args(["cmd", "--alias", "gitx  gs", "gti"])
+        .env("PATH", &bin)
+        .env("DYM_CONFIG", dir.join("config.toml"))
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(127));
+    assert!(output.stdout.is_empty());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stderr),
+        "gti: command not found\nDid you mean?\n1. git\n"
+    );
+
+    // The number configuration key limits the suggestions unless --number is given.
+    write(dir.join("config.toml"), "number = 1\n").unwrap();
+    let cmd = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_dym"))
+            .args(args)
+            .env("PATH", &bin)
+            .env("DYM_CONFIG", dir.join("config.toml"))
+            .env("NO_COLOR", "1")
+            .output()
+            .unwrap()
+    };
+    assert_eq!(
+        String::from_utf8_lossy(&cmd(&["cmd", "--alias", "gitx", "gitt"]).stderr),
+        "gitt: command not found\nDid you mean?\n1. gitx\n"
+    );
+    assert_eq!(
+        String::from_utf8_lossy(&cmd(&["cmd", "-n", "2", "--alias", "gitx", "gitt"]).stderr),
+        "gitt: command not found\nDid you mean?\n1. gitx\n2. git\n"
+    );
+
+    // Aliases can't be run by the prompting handlers, so they aren't suggested there.
+    let output = dym(&dir, &["cmd", "--hook", "bash", "--prompt"]);
+    assert!(output.status.success());
+    let hook = String::from_utf8_lossy(&output.stdout);
+    assert!(hook.starts_with("command_not_found_handle() {\n    local fixed\n"));
+    assert!(hook.contains("compgen -A function"));
+    let output = dym(&dir, &["cmd", "--hook", "zsh", "--prompt"]);
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("aliases"));
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn git_hook_test() {
+    let dir = word_list_dir("git-hook", WORDS);
+    let repo = dir.join("repo");
+    create_dir_all(&repo).unwrap();
+    assert!(Command::new("git")
+        .args(["init", "-q"])
+        .current_dir(&repo)
+        .status()
+        .unwrap()
+        .success());
+    write(repo.join(".dym-words"), "wrld\n").unwrap();
+    write(
+        repo.join("message"),
+        "The wrld\n\nhello wurd\n# the hlep text\n\nSigned-off-by: Hlep <a@b.c>\n\
+         # ------------------------ >8 ------------------------\ndiff --git a/hlep b/hlep\n",
+    )
+    .unwrap();
+
+    let git_hook = |args: &[&str]| {
+        command(&dir)
+            .args(args)
+            .current_dir(&repo)
+            .output()
+            .unwrap()
+    };
+
+    // Only the misspelled word in the prose is reported, and only aborts in strict mode.
+    let output = git_hook(&["git-hook", "commit-msg", "-n", "1", "message"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).starts_with("message:3:7: wurd -> word\n"));
+    let output = git_hook(&["git-hook", "commit-msg", "--strict", "message"]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = git_hook(&["git-hook", "install", "--strict"]);
+    assert!(output.status.success());
+    let hook = std::fs::read_to_string(repo.join(".git/hooks/commit-msg")).unwrap();
+    assert!(hook.ends_with("exec dym git-hook commit-msg --strict \"$1\"\n"));
+
+    // Hooks that weren't installed by dym are kept unless forced.
+    write(repo.join(".git/hooks/commit-msg"), "#!/bin/sh\n").unwrap();
+    assert_eq!(git_hook(&["git-hook", "install"]).status.code(), Some(1));
+    assert!(git_hook(&["git-hook", "install", "--force"])
+        .status
+        .success());
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn check_formats_test() {
+    let dir = word_list_dir("formats", WORDS);
+    write(dir.join("notes.txt"), "the wrld\nhlep & <x>\n").unwrap();
+    let file = dir.join("notes.txt");
+    let file = file.to_str().unwrap();
+
+    let output = dym(&dir, &["check", "-n", "1", "-f", "checkstyle", file]);
+    assert_eq!(output.status.code(), Some(1));
+    let checkstyle = String::from_utf8_lossy(&output.stdout);
+    assert!(checkstyle.contains(
+        r#"<error line="2" column="1" severity="warning" message="Unknown word &quot;hlep&quot;, did you mean help?" source="didyoumean.misspelling"/>"#
+    ));
+    assert_eq!(checkstyle.matches("<error ").count(), 3);
+
+    let output = dym(&dir, &["check", "-n", "1", "-f", "sarif", file]);
+    assert_eq!(output.status.code(), Some(1));
+    let sarif: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let result = &sarif["runs"][0]["results"][0];
+    assert_eq!(
+        result["message"]["text"],
+        "Unknown word \"wrld\", did you mean world?"
+    );
+    let location = &result["locations"][0]["physicalLocation"];
+    assert!(location["artifactLocation"]["uri"]
+        .as_str()
+        .unwrap()
+        .ends_with("/notes.txt"));
+    assert_eq!(location["region"]["startColumn"], 5);
+    assert_eq!(location["region"]["endColumn"], 9);
+
+    // Paths are percent-encoded, absolute ones as file URIs, and standard input has no URI.
+    write(dir.join("my notes#1.txt"), "wrld\n").unwrap();
+    let output = command(&dir)
+        .args(["check", "-f", "sarif", "my notes#1.txt"])
+        .arg(dir.join("my notes#1.txt"))
+        .current_dir(&dir)
+        .output()
+        .unwrap();
+    let sarif: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let uri = |i: usize| {
+        sarif["runs"][0]["results"][i]["locations"][0]["physicalLocation"]["artifactLocation"]
+            ["uri"]
+            .clone()
+    };
+    assert_eq!(uri(0), "my%20notes%231.txt");
+    let absolute = uri(1);
+    assert!(absolute.as_str().unwrap().starts_with("file:///"));
+    assert!(absolute.as_str().unwrap().ends_with("/my%20notes%231.txt"));
+
+    let output = dym_with_input(&dir, &["check", "-f", "sarif"], "wrld\n");
+    let sarif: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let location =
+        &sarif["runs"][0]["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"];
+    assert!(location.get("uri").is_none());
+    assert_eq!(location["description"]["text"], "Standard input");
+
+    assert_eq!(dym(&dir, &["check", "-f", "json"]).status.code(), Some(2));
+
+    remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,74 @@
+#![cfg(all(feature = "clipboard", unix))]
+
+mod common;
+
+use common::{command, word_list_dir};
+use std::{
+    fs::{read_to_string, remove_dir_all, set_permissions, write, Permissions},
+    os::unix::fs::PermissionsExt,
+    path::Path,
+    process::Output,
+};
+
+/// Stand in for a clipboard command with a script saving its arguments and what it is given.
+fn fake_command(dir: &Path, program: &str) {
+    let script = dir.join(program);
+    write(
+        &script,
+        format!(
+            "#!/bin/sh\necho \"$@\" > '{0}/{1}.args'\n/bin/cat > '{0}/{1}.out'\n",
+            dir.display(),
+            program
+        ),
+    )
+    .unwrap();
+    set_permissions(&script, Permissions::from_mode(0o755)).unwrap();
+}
+
+/// Run `dym` with `args` against the word list in `dir`, with only the fake commands in `dir` on
+/// `$PATH` and an X11 display.
+fn dym_with_commands(dir: &Path, args: &[&str]) -> Output {
+    command(dir)
+        .args(args)
+        .env("PATH", dir)
+        .env("DISPLAY", ":0")
+        .env_remove("WAYLAND_DISPLAY")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn command_backend_test() {
+    let dir = word_list_dir("clipboard", "hello\nworld\n");
+    fake_command(&dir, "wl-copy");
+    fake_command(&dir, "xclip");
+
+    write(dir.join("config.toml"), "clipboard = \"wl-copy\"\n").unwrap();
+    assert!(dym_with_commands(&dir, &["helo", "-y", "--pick", "1"])
+        .status
+        .success());
+    assert_eq!(read_to_string(dir.join("wl-copy.out")).unwrap(), "hello");
+
+    // Commands that aren't installed can't be used.
+    write(dir.join("config.toml"), "clipboard = \"xsel\"\n").unwrap();
+    let output = dym_with_commands(&dir, &["helo", "-y", "--pick", "1"]);
+    assert!(!output.status.success());
+
+    // The command for the running display server is preferred, and is asked for the primary
+    // selection.
+    write(
+        dir.join("config.toml"),
+        "clipboard_selection = \"primary\"\n",
+    )
+    .unwrap();
+    assert!(dym_with_commands(&dir, &["wrld", "-y", "--pick", "1"])
+        .status
+        .success());
+    assert_eq!(read_to_string(dir.join("xclip.out")).unwrap(), "world");
+    assert_eq!(
+        read_to_string(dir.join("xclip.args")).unwrap(),
+        "-selection primary\n"
+    );
+
+    remove_dir_all(&dir).unwrap();
+}
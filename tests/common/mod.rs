@@ -0,0 +1,62 @@
+//! Helpers shared by the integration tests.
+#![allow(dead_code)]
+
+use std::{
+    fs::{create_dir_all, remove_dir_all, write},
+    io::Write,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+};
+
+/// Create an empty data directory for `name`, unique to the test run.
+pub fn data_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("dym-{}-{}", name, std::process::id()));
+    remove_dir_all(&dir).ok();
+    create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Create a data directory for `name` holding `words` as the English word list.
+pub fn word_list_dir(name: &str, words: &str) -> PathBuf {
+    let dir = data_dir(name);
+    write(dir.join("en"), words).unwrap();
+    dir
+}
+
+/// Return a `dym` command using only the word lists and configuration file in `data_dir`,
+/// without colors. Nothing is downloaded unless the test gives a source of its own.
+pub fn command(data_dir: &Path) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_dym"));
+    command
+        .env("DYM_DATA_DIR", data_dir)
+        .env("DYM_SYSTEM_DATA_DIRS", "")
+        .env("DYM_CONFIG", data_dir.join("config.toml"))
+        .env("DYM_SOURCE_URL", "http://127.0.0.1:9")
+        .env_remove("DYM_MIRRORS")
+        .env("NO_PROXY", "127.0.0.1")
+        .env("NO_COLOR", "1");
+    command
+}
+
+/// Run `dym` with `args` against `data_dir`.
+pub fn dym(data_dir: &Path, args: &[&str]) -> Output {
+    command(data_dir).args(args).output().unwrap()
+}
+
+/// Run `dym` with `args` against `data_dir`, feeding it `stdin`.
+pub fn dym_with_input(data_dir: &Path, args: &[&str], stdin: &str) -> Output {
+    let mut child = command(data_dir)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    child.wait_with_output().unwrap()
+}
@@ -0,0 +1,105 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{command, dym, word_list_dir};
+use std::{
+    fs::{remove_dir_all, write},
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    process::{Child, Stdio},
+    thread::sleep,
+    time::Duration,
+};
+
+/// Create a directory for `name` holding a data directory with `words` as the English word list
+/// and a configuration file pointing to a shared socket.
+fn dir(name: &str, words: &str, socket: &Path) -> PathBuf {
+    let dir = word_list_dir(name, words);
+    write(
+        dir.join("config.toml"),
+        format!("socket = \"{}\"\n", socket.display()),
+    )
+    .unwrap();
+    dir
+}
+
+/// Send a request line to the daemon and return its response line.
+fn request(socket: &Path, line: &str) -> String {
+    let mut stream = UnixStream::connect(socket).unwrap();
+    stream.write_all(format!("{}\n", line).as_bytes()).unwrap();
+    let mut response = String::new();
+    BufReader::new(stream).read_line(&mut response).unwrap();
+    response
+}
+
+/// Kill the daemon when the test ends, even if it fails.
+struct Daemon(Child);
+
+impl Drop for Daemon {
+    fn drop(&mut self) {
+        self.0.kill().ok();
+        self.0.wait().ok();
+    }
+}
+
+#[test]
+fn daemon_test() {
+    let socket = std::env::temp_dir().join(format!("dym-daemon-{}.sock", std::process::id()));
+    let server = dir("server", "hello\nhelp\n", &socket);
+    let client = dir("client", "world\n", &socket);
+
+    let daemon = Daemon(
+        command(&server)
+            .arg("daemon")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap(),
+    );
+    for _ in 0..100 {
+        if UnixStream::connect(&socket).is_ok() {
+            break;
+        }
+        sleep(Duration::from_millis(50));
+    }
+
+    assert_eq!(
+        request(
+            &socket,
+            r#"{"command": "suggest", "word": "helo", "number": 1}"#
+        ),
+        "{\"suggestions\":[{\"word\":\"hello\",\"distance\":1}]}\n"
+    );
+    assert!(request(&socket, r#"{"command": "ping"}"#).starts_with("{\"version\":"));
+    assert!(request(&socket, "helo").starts_with("{\"error\":\"Invalid request"));
+
+    // Asking for too many suggestions is refused without taking the daemon down, and asking for
+    // more than there are words returns all of them.
+    assert!(request(
+        &socket,
+        r#"{"command": "suggest", "word": "helo", "number": 1099511627776}"#
+    )
+    .starts_with("{\"error\":\"number can't be larger than"));
+    assert_eq!(
+        request(
+            &socket,
+            r#"{"command": "suggest", "word": "helo", "number": 1000}"#
+        ),
+        "{\"suggestions\":[{\"word\":\"hello\",\"distance\":1},{\"word\":\"help\",\"distance\":1}]}\n"
+    );
+
+    // The client is answered from the daemon's word list rather than its own.
+    let output = dym(&client, &["-c", "helo"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\nhelp\n");
+
+    // Without a daemon, the client searches its own word list.
+    drop(daemon);
+    let output = dym(&client, &["-c", "helo"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "world\n");
+
+    remove_dir_all(&server).unwrap();
+    remove_dir_all(&client).unwrap();
+    std::fs::remove_file(&socket).ok();
+}
@@ -0,0 +1,32 @@
+use didyoumean::data::DataDirs;
+use std::fs::{create_dir_all, remove_dir_all, write};
+
+#[test]
+fn find_prefers_system_dirs_test() {
+    let root = std::env::temp_dir().join(format!("dym-data-dirs-{}", std::process::id()));
+    let user = root.join("user");
+    let system = root.join("system");
+    create_dir_all(&user).unwrap();
+    create_dir_all(&system).unwrap();
+
+    write(user.join("en"), "user").unwrap();
+    write(user.join("fr"), "user").unwrap();
+    write(system.join("en"), "system").unwrap();
+
+    let dirs = DataDirs::with_dirs(Some(user.clone()), vec![system.clone()]);
+
+    // Preinstalled lists shadow downloaded ones, and missing lists fall back to the user directory.
+    assert_eq!(dirs.find("en"), Some(system.join("en")));
+    assert_eq!(dirs.find("fr"), Some(user.join("fr")));
+    assert_eq!(dirs.find("de"), None);
+
+    remove_dir_all(&root).unwrap();
+}
+
+#[test]
+fn missing_user_dir_test() {
+    let dirs = DataDirs::with_dirs(None, vec![]);
+
+    assert_eq!(dirs.find("en"), None);
+    assert!(dirs.ensure_user().is_err());
+}
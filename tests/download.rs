@@ -0,0 +1,600 @@
+#![cfg(feature = "download")]
+
+mod common;
+
+use common::{command, data_dir, dym};
+use didyoumean::compression::Compression;
+use sha2::{Digest, Sha256};
+use std::{
+    fs::{create_dir_all, read, read_dir, read_to_string, remove_dir_all, write},
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+/// A request received by the stand-in server.
+#[derive(Debug, Clone)]
+struct Request {
+    path: String,
+    /// Header names are lowercased.
+    headers: Vec<(String, String)>,
+}
+
+impl Request {
+    /// Return the value of the header `name`, given in lowercase.
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header == name)
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Start an HTTP server on a random local port that answers every request with the raw bytes
+/// returned by `handler`. Return its base URL and the requests it received.
+fn serve<F>(handler: F) -> (String, Arc<Mutex<Vec<Request>>>)
+where
+    F: Fn(&Request) -> Vec<u8> + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(vec![]));
+    let received = requests.clone();
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            // Read the request line and the headers.
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let path = line.split_whitespace().nth(1).unwrap_or("/").to_owned();
+            let mut headers = vec![];
+            loop {
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+                if line.trim_end().is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':') {
+                    headers.push((name.trim().to_lowercase(), value.trim().to_owned()));
+                }
+            }
+
+            let request = Request { path, headers };
+            received.lock().unwrap().push(request.clone());
+            stream.write_all(&handler(&request)).ok();
+        }
+    });
+
+    (url, requests)
+}
+
+/// Build a raw HTTP response with the given status line, extra headers and body.
+fn response(status: &str, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+    let mut response = format!(
+        "HTTP/1.1 {}\r\nContent-Length: {}\r\nConnection: close\r\n",
+        status,
+        body.len()
+    );
+    for (name, value) in headers {
+        response.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    response.push_str("\r\n");
+
+    let mut response = response.into_bytes();
+    response.extend_from_slice(body);
+    response
+}
+
+/// Serve `word_list` as the English word list, and 404 for everything else.
+fn word_list_handler(word_list: &'static str) -> impl Fn(&Request) -> Vec<u8> {
+    move |request| match request.path.as_str() {
+        "/en" => response("200 OK", &[], word_list.as_bytes()),
+        _ => response("404 Not Found", &[], b""),
+    }
+}
+
+/// Return the names of the files in `dir`, sorted.
+fn files(dir: &PathBuf) -> Vec<String> {
+    let mut files: Vec<String> = read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn download_from_source_url_test() {
+    let (url, _) = serve(word_list_handler("hello\nworld\n"));
+    let dir = data_dir("source");
+
+    let output = dym(&dir, &["--source-url", &url, "lang", "install", "en"]);
+    assert!(output.status.success());
+    assert_eq!(read_to_string(dir.join("en")).unwrap(), "hello\nworld\n");
+    assert_eq!(files(&dir), vec!["en", "en.meta"]);
+
+    // The installed word list is used for suggestions.
+    let output = dym(&dir, &["--source-url", &url, "helo", "-c", "-n", "1"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn mirror_fallback_test() {
+    let (broken, _) = serve(|_| response("500 Internal Server Error", &[], b""));
+    let (mirror, _) = serve(word_list_handler("hello\n"));
+    let dir = data_dir("mirror");
+
+    let output = dym(
+        &dir,
+        &[
+            "--source-url",
+            &broken,
+            "--mirror",
+            &mirror,
+            "lang",
+            "install",
+            "en",
+        ],
+    );
+    assert!(output.status.success());
+    assert_eq!(read_to_string(dir.join("en")).unwrap(), "hello\n");
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn file_source_test() {
+    let source = data_dir("file-source");
+    write(source.join("en"), "hello\n").unwrap();
+    let dir = data_dir("file");
+
+    let url = format!("file://{}", source.display());
+    let output = dym(&dir, &["--source-url", &url, "lang", "install", "en"]);
+    assert!(output.status.success());
+    assert_eq!(read_to_string(dir.join("en")).unwrap(), "hello\n");
+
+    remove_dir_all(&source).unwrap();
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn checksum_mismatch_test() {
+    let (url, _) = serve(|request| match request.path.as_str() {
+        "/en" => response("200 OK", &[], b"hello\n"),
+        "/SHA256SUMS" => {
+            let manifest = format!("{:x}  en\n", Sha256::digest(b"other\n"));
+            response("200 OK", &[], manifest.as_bytes())
+        }
+        _ => response("404 Not Found", &[], b""),
+    });
+    let dir = data_dir("checksum");
+
+    let output = dym(&dir, &["--source-url", &url, "lang", "install", "en"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("expected checksum"));
+
+    // Nothing is left behind, not even the temporary file.
+    assert!(files(&dir).is_empty());
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn truncated_download_test() {
+    // Announce more bytes than are sent before closing the connection.
+    let (url, _) = serve(|_| b"HTTP/1.1 200 OK\r\nContent-Length: 100\r\n\r\nhello\n".to_vec());
+    let dir = data_dir("truncated");
+
+    let output = dym(&dir, &["--source-url", &url, "lang", "install", "en"]);
+    assert!(!output.status.success());
+    assert!(files(&dir).is_empty());
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn corrupt_word_list_redownload_test() {
+    let (url, requests) = serve(word_list_handler("hello\n"));
+    let dir = data_dir("corrupt");
+
+    assert!(dym(&dir, &["--source-url", &url, "lang", "install", "en"])
+        .status
+        .success());
+
+    // Simulate a word list that was cut short after it was installed. The sidecar still records
+    // the size of the download, which no longer matches.
+    assert!(dir.join("en.meta").is_file());
+    write(dir.join("en"), "hel").unwrap();
+    let output = dym(&dir, &["--source-url", &url, "helo", "-c", "-n", "1"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("corrupt"));
+    assert!(String::from_utf8_lossy(&output.stdout).ends_with("\nhello\n"));
+    assert!(!String::from_utf8_lossy(&output.stdout).contains("corrupt"));
+    assert_eq!(read_to_string(dir.join("en")).unwrap(), "hello\n");
+
+    let downloads = requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|request| request.path == "/en")
+        .count();
+    assert_eq!(downloads, 2);
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn update_unchanged_test() {
+    let (url, requests) = serve(|request| match request.path.as_str() {
+        "/en" if request.header("if-none-match") == Some("\"v1\"") => {
+            response("304 Not Modified", &[("ETag", "\"v1\"")], b"")
+        }
+        "/en" => response("200 OK", &[("ETag", "\"v1\"")], b"hello\n"),
+        _ => response("404 Not Found", &[], b""),
+    });
+    let dir = data_dir("unchanged");
+
+    assert!(dym(&dir, &["--source-url", &url, "lang", "install", "en"])
+        .status
+        .success());
+    assert!(read_to_string(dir.join("en.meta"))
+        .unwrap()
+        .contains("etag = '\"v1\"'"));
+
+    let output = dym(&dir, &["--source-url", &url, "lang", "update"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Unchanged: English"));
+    assert_eq!(read_to_string(dir.join("en")).unwrap(), "hello\n");
+
+    // The update sent the stored validator.
+    let requests = requests.lock().unwrap();
+    let last = requests.iter().rev().find(|request| request.path == "/en");
+    assert_eq!(last.unwrap().header("if-none-match"), Some("\"v1\""));
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn update_changed_test() {
+    let (old, _) = serve(word_list_handler("hello\n"));
+    let (new, _) = serve(word_list_handler("hello\nworld\n"));
+    let dir = data_dir("changed");
+
+    assert!(dym(&dir, &["--source-url", &old, "lang", "install", "en"])
+        .status
+        .success());
+
+    let output = dym(&dir, &["--source-url", &new, "lang", "update"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Updated: English"));
+    assert_eq!(read_to_string(dir.join("en")).unwrap(), "hello\nworld\n");
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn update_failure_keeps_word_list_test() {
+    let (url, _) = serve(word_list_handler("hello\n"));
+    let (broken, _) = serve(|_| response("500 Internal Server Error", &[], b""));
+    let dir = data_dir("update-failure");
+
+    assert!(dym(&dir, &["--source-url", &url, "lang", "install", "en"])
+        .status
+        .success());
+
+    let output = dym(&dir, &["--source-url", &broken, "lang", "update"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Failed: English"));
+    assert_eq!(read_to_string(dir.join("en")).unwrap(), "hello\n");
+    assert_eq!(files(&dir), vec!["en", "en.meta"]);
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn concurrent_install_test() {
+    let (url, requests) = serve(|request| match request.path.as_str() {
+        "/en" | "/fr" | "/de" => response("200 OK", &[], &request.path.as_bytes()[1..]),
+        _ => response("404 Not Found", &[], b""),
+    });
+    let dir = data_dir("concurrent");
+
+    let output = dym(
+        &dir,
+        &[
+            "--source-url",
+            &url,
+            "lang",
+            "install",
+            "en",
+            "fr",
+            "de",
+            "en",
+        ],
+    );
+    assert!(output.status.success());
+    for lang in ["en", "fr", "de"] {
+        assert_eq!(read_to_string(dir.join(lang)).unwrap(), lang);
+    }
+
+    // Repeated languages are only downloaded once.
+    let downloads = requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|request| request.path == "/en")
+        .count();
+    assert_eq!(downloads, 1);
+
+    let output = dym(&dir, &["--source-url", &url, "lang", "update"]);
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Updated: German, English, French"));
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn retry_test() {
+    // Fail the first request for the word list.
+    let attempts = AtomicUsize::new(0);
+    let (url, _) = serve(move |request| match request.path.as_str() {
+        "/en" if attempts.fetch_add(1, Ordering::SeqCst) == 0 => {
+            response("503 Service Unavailable", &[], b"")
+        }
+        "/en" => response("200 OK", &[], b"hello\n"),
+        _ => response("404 Not Found", &[], b""),
+    });
+    let dir = data_dir("retry");
+
+    let output = dym(
+        &dir,
+        &[
+            "--source-url",
+            &url,
+            "--retries",
+            "1",
+            "lang",
+            "install",
+            "en",
+        ],
+    );
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("Retrying in 500ms (1/1)"));
+    assert_eq!(read_to_string(dir.join("en")).unwrap(), "hello\n");
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn no_retry_on_client_error_test() {
+    let (url, requests) = serve(|_| response("404 Not Found", &[], b""));
+    let dir = data_dir("no-retry");
+
+    let output = dym(&dir, &["--source-url", &url, "lang", "install", "en"]);
+    assert!(!output.status.success());
+    let downloads = requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|request| request.path == "/en")
+        .count();
+    assert_eq!(downloads, 1);
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn resume_test() {
+    let (url, requests) = serve(|request| match request.path.as_str() {
+        // Send the rest of the word list if the client asks for it.
+        "/en" if request.header("range") == Some("bytes=6-") => response(
+            "206 Partial Content",
+            &[("ETag", "\"v1\""), ("Content-Range", "bytes 6-11/12")],
+            b"world\n",
+        ),
+        // Otherwise close the connection halfway through.
+        "/en" => b"HTTP/1.1 200 OK\r\nContent-Length: 12\r\nETag: \"v1\"\r\n\r\nhello\n".to_vec(),
+        _ => response("404 Not Found", &[], b""),
+    });
+    let dir = data_dir("resume");
+
+    let output = dym(
+        &dir,
+        &[
+            "--source-url",
+            &url,
+            "--retries",
+            "1",
+            "lang",
+            "install",
+            "en",
+        ],
+    );
+    assert!(output.status.success());
+    assert_eq!(read_to_string(dir.join("en")).unwrap(), "hello\nworld\n");
+
+    // The checksum covers the whole word list, not just the resumed part.
+    let sha256 = format!("{:x}", Sha256::digest(b"hello\nworld\n"));
+    assert!(read_to_string(dir.join("en.meta"))
+        .unwrap()
+        .contains(&sha256));
+
+    let requests = requests.lock().unwrap();
+    let resumed = requests
+        .iter()
+        .find(|request| request.header("range").is_some());
+    assert_eq!(resumed.unwrap().header("if-range"), Some("\"v1\""));
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unknown_length_test() {
+    // Send the word list without a Content-Length header, ending it by closing the connection.
+    let (url, _) = serve(|request| match request.path.as_str() {
+        "/en" => b"HTTP/1.1 200 OK\r\nConnection: close\r\n\r\nhello\n".to_vec(),
+        _ => response("404 Not Found", &[], b""),
+    });
+    let dir = data_dir("unknown-length");
+
+    let output = dym(&dir, &["--source-url", &url, "lang", "install", "en"]);
+    assert!(output.status.success());
+    assert_eq!(read_to_string(dir.join("en")).unwrap(), "hello\n");
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn zero_timeout_test() {
+    let dir = data_dir("zero-timeout");
+
+    let output = dym(&dir, &["--timeout", "0", "lang", "install", "en"]);
+    assert_eq!(output.status.code(), Some(2));
+
+    write(dir.join("config.toml"), "timeout = 0\n").unwrap();
+    let output = dym(&dir, &["lang", "list"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("timeout must be at least 1 second"));
+    assert!(!dym(&dir, &["config", "set", "timeout", "0"])
+        .status
+        .success());
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn proxy_test() {
+    // A proxy is sent the full URL of the word list, which it fetches on the client's behalf.
+    let (proxy, requests) = serve(|request| match request.path.as_str() {
+        "http://wordlists.invalid/en" => response("200 OK", &[], b"hello\n"),
+        _ => response("404 Not Found", &[], b""),
+    });
+    let dir = data_dir("proxy");
+
+    write(dir.join("config.toml"), format!("proxy = \"{}\"\n", proxy)).unwrap();
+    let output = dym(
+        &dir,
+        &[
+            "--source-url",
+            "http://wordlists.invalid",
+            "--retries",
+            "0",
+            "lang",
+            "install",
+            "en",
+        ],
+    );
+    assert!(output.status.success());
+    assert_eq!(read_to_string(dir.join("en")).unwrap(), "hello\n");
+    assert!(requests
+        .lock()
+        .unwrap()
+        .iter()
+        .any(|request| request.path == "http://wordlists.invalid/en"));
+
+    // Without the proxy key, the proxy environment variables are used.
+    remove_dir_all(&dir).unwrap();
+    create_dir_all(&dir).unwrap();
+    let output = command(&dir)
+        .args(["--source-url", "http://wordlists.invalid", "--retries", "0"])
+        .args(["lang", "install", "en"])
+        .env("HTTP_PROXY", &proxy)
+        .env("NO_PROXY", "")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(read_to_string(dir.join("en")).unwrap(), "hello\n");
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn timeout_test() {
+    let (url, _) = serve(|request| {
+        if request.path == "/en" {
+            thread::sleep(Duration::from_secs(3));
+        }
+        response("404 Not Found", &[], b"")
+    });
+    let dir = data_dir("timeout");
+
+    let output = dym(
+        &dir,
+        &[
+            "--source-url",
+            &url,
+            "--timeout",
+            "1",
+            "--retries",
+            "0",
+            "lang",
+            "install",
+            "en",
+        ],
+    );
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("timed out"));
+    assert!(files(&dir).is_empty());
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn compressed_download_test() {
+    let (url, requests) = serve(|request| match request.path.as_str() {
+        "/en.zst" => {
+            let compressed = Compression::Zstd.compress(b"hello\nworld\n").unwrap();
+            response("200 OK", &[], &compressed)
+        }
+        "/SHA256SUMS" => {
+            let manifest = format!("{:x}  en\n", Sha256::digest(b"hello\nworld\n"));
+            response("200 OK", &[], manifest.as_bytes())
+        }
+        _ => response("404 Not Found", &[], b""),
+    });
+    let dir = data_dir("compressed");
+
+    let output = dym(&dir, &["--source-url", &url, "lang", "install", "en"]);
+    assert!(output.status.success());
+    assert_eq!(read_to_string(dir.join("en")).unwrap(), "hello\nworld\n");
+
+    // The plain word list wasn't requested.
+    let requests = requests.lock().unwrap();
+    assert!(!requests.iter().any(|request| request.path == "/en"));
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn compressed_storage_test() {
+    let (url, _) = serve(|request| match request.path.as_str() {
+        "/en.gz" => {
+            let compressed = Compression::Gzip.compress(b"hello\nworld\n").unwrap();
+            response("200 OK", &[], &compressed)
+        }
+        _ => response("404 Not Found", &[], b""),
+    });
+    let dir = data_dir("compressed-storage");
+    write(dir.join("config.toml"), "compression = \"zstd\"\n").unwrap();
+
+    let output = dym(&dir, &["--source-url", &url, "lang", "install", "en"]);
+    assert!(output.status.success());
+    let stored = read(dir.join("en")).unwrap();
+    assert_eq!(Compression::detect(&stored), Compression::Zstd);
+
+    // Compressed word lists are decompressed when they are loaded.
+    let output = dym(&dir, &["--source-url", &url, "helo", "-c", "-n", "1"]);
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+
+    remove_dir_all(&dir).unwrap();
+}
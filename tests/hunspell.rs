@@ -0,0 +1,132 @@
+use didyoumean::{data::DataDirs, hunspell};
+use std::{
+    collections::HashMap,
+    fs::{create_dir_all, remove_dir_all, write},
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    process::{Command, Stdio},
+};
+
+#[test]
+fn affix_expansion_test() {
+    // Flags are numbers, stems refer to flag sets by alias, and suffixes can be followed by
+    // another suffix.
+    let aff = "FLAG num\nAF 2\nAF 1,3\nAF 2\nNEEDAFFIX 3\n\
+               SFX 1 Y 1\nSFX 1 e ing/2 [^e]e\n\
+               SFX 2 Y 1\nSFX 2 0 s .\n\
+               PFX 3 N 1\nPFX 3 0 re .\n";
+    let dictionary = hunspell::parse(aff, "3\nmake/1\ncode/2\nwrite/1 po:verb\n");
+
+    let mut words: Vec<&str> = dictionary.words().collect();
+    words.sort();
+    assert_eq!(
+        words,
+        vec!["code", "codes", "making", "makings", "remake", "rewrite", "writing", "writings"]
+    );
+}
+
+#[test]
+fn load_and_use_test() {
+    let dir = std::env::temp_dir().join(format!("dym-hunspell-{}", std::process::id()));
+    let hunspell_dir = dir.join("hunspell");
+    create_dir_all(&hunspell_dir).unwrap();
+
+    // ISO8859-1 is decoded, and a variant has to be chosen when there are several.
+    write(
+        hunspell_dir.join("pt_PT.aff"),
+        b"SET ISO8859-1\nSFX S Y 1\nSFX S 0 s .\n",
+    )
+    .unwrap();
+    write(hunspell_dir.join("pt_PT.dic"), b"1\ncaf\xe9/S\n").unwrap();
+    write(hunspell_dir.join("pt_BR.aff"), "SET UTF-8\n").unwrap();
+    write(hunspell_dir.join("pt_BR.dic"), "1\nchá\n").unwrap();
+
+    let data_dirs =
+        DataDirs::with_dirs(None, vec![]).with_hunspell_dirs(vec![hunspell_dir.clone()]);
+    assert_eq!(data_dirs.find_hunspell("pt"), None);
+    assert_eq!(data_dirs.hunspell_variants("pt"), vec!["pt_BR", "pt_PT"]);
+    let data_dirs =
+        data_dirs.with_hunspell_names(HashMap::from([("pt".to_owned(), "pt_PT".to_owned())]));
+    let dic = data_dirs.find_hunspell("pt").unwrap();
+    assert_eq!(dic, hunspell_dir.join("pt_PT.dic"));
+    assert!(hunspell::load(&dic).unwrap().contains("cafés"));
+    assert_eq!(data_dirs.find_hunspell("he"), None);
+
+    // The only variant of a language is used, and other encodings are decoded too.
+    write(hunspell_dir.join("pl_PL.aff"), "SET ISO8859-2\n").unwrap();
+    write(hunspell_dir.join("pl_PL.dic"), b"1\nr\xeaka\n").unwrap();
+    write(hunspell_dir.join("ru_RU.aff"), "SET microsoft-cp1251\n").unwrap();
+    write(hunspell_dir.join("ru_RU.dic"), b"1\n\xec\xe8\xf0\n").unwrap();
+    let dic = data_dirs.find_hunspell("pl").unwrap();
+    assert!(hunspell::load(&dic).unwrap().contains("ręka"));
+    let dic = data_dirs.find_hunspell("ru").unwrap();
+    assert!(hunspell::load(&dic).unwrap().contains("мир"));
+
+    // Languages without a word list to download use the Hunspell dictionary.
+    let dym = || {
+        Command::new(env!("CARGO_BIN_EXE_dym"))
+            .args(["-l", "pt", "-c", "-n", "1", "cafes"])
+            .env("DYM_DATA_DIR", dir.join("data"))
+            .env("DYM_SYSTEM_DATA_DIRS", "")
+            .env("DYM_HUNSPELL_DIRS", &hunspell_dir)
+            .env("DYM_CONFIG", dir.join("config.toml"))
+            .output()
+            .unwrap()
+    };
+    let output = dym();
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("There are Hunspell dictionaries for several variants"));
+    assert!(stderr.contains(": pt_BR, pt_PT. Choose one with `dym config set hunspell"));
+
+    write(dir.join("config.toml"), "hunspell = {pt = \"pt_PT\"}\n").unwrap();
+    let output = dym();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "cafés\n");
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn serve_test() {
+    let dir = std::env::temp_dir().join(format!("dym-hunspell-serve-{}", std::process::id()));
+    let hunspell_dir = dir.join("hunspell");
+    create_dir_all(&hunspell_dir).unwrap();
+    write(hunspell_dir.join("he_IL.aff"), "SET UTF-8\n").unwrap();
+    write(hunspell_dir.join("he_IL.dic"), "1\nשלום\n").unwrap();
+
+    // A language only available as a Hunspell dictionary is loaded at startup.
+    let mut server = Command::new(env!("CARGO_BIN_EXE_dym"))
+        .args(["serve", "he", "--bind", "127.0.0.1:0"])
+        .env("DYM_DATA_DIR", dir.join("data"))
+        .env("DYM_SYSTEM_DATA_DIRS", "")
+        .env("DYM_HUNSPELL_DIRS", &hunspell_dir)
+        .env("DYM_CONFIG", dir.join("config.toml"))
+        .env("NO_COLOR", "1")
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut line = String::new();
+    BufReader::new(server.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let address = line.trim().strip_prefix("Listening on http://");
+
+    let response = address.map(|address| {
+        let mut stream = TcpStream::connect(address).unwrap();
+        stream
+            .write_all(b"GET /suggest?q=%D7%A9%D7%9C%D7%95&lang=he&n=1 HTTP/1.1\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        response
+    });
+    server.kill().ok();
+    server.wait().ok();
+
+    assert!(response
+        .unwrap()
+        .ends_with("{\"lang\":\"he\",\"suggestions\":[{\"word\":\"שלום\",\"distance\":1}]}"));
+
+    remove_dir_all(&dir).unwrap();
+}
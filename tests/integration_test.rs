@@ -1,3 +1,5 @@
+#![cfg(feature = "clipboard")]
+
 use cli_clipboard::{ClipboardContext, ClipboardProvider};
 use didyoumean::yank;
 
@@ -14,7 +16,7 @@ fn yank_test() {
     std::thread::sleep(std::time::Duration::from_secs(1));
 
     // Get the clipboard contents.
-    let clipboard = format!("{}", ctx.get_contents().unwrap());
+    let clipboard = ctx.get_contents().unwrap();
 
     assert_eq!(clipboard, string);
 
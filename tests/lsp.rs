@@ -0,0 +1,236 @@
+mod common;
+
+use common::{command, data_dir};
+use std::{
+    fs::{read_to_string, remove_dir_all, write},
+    io::{BufRead, BufReader, Read, Write},
+    process::{ChildStdout, Stdio},
+};
+
+/// Send a JSON-RPC message framed with a `Content-Length` header.
+fn send(input: &mut impl Write, message: &str) {
+    write!(
+        input,
+        "Content-Length: {}\r\n\r\n{}",
+        message.len(),
+        message
+    )
+    .unwrap();
+    input.flush().unwrap();
+}
+
+/// Read the next JSON-RPC message.
+fn receive(output: &mut BufReader<ChildStdout>) -> String {
+    let mut length = 0;
+    loop {
+        let mut line = String::new();
+        output.read_line(&mut line).unwrap();
+        // Anything but headers would break the framing of the messages.
+        match line.trim_end().split_once(": ") {
+            Some(("Content-Length", value)) => length = value.parse().unwrap(),
+            Some(("Content-Type", _)) => {}
+            _ if line.trim_end().is_empty() => break,
+            _ => panic!("Unexpected output: {:?}", line),
+        }
+    }
+    let mut body = vec![0; length];
+    output.read_exact(&mut body).unwrap();
+    String::from_utf8(body).unwrap()
+}
+
+#[test]
+fn lsp_test() {
+    let dir = data_dir("lsp");
+    write(dir.join("en"), "the\nworld\nword\n").unwrap();
+
+    let mut child = command(&dir)
+        .args(["lsp", "-n", "2"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut input = child.stdin.take().unwrap();
+    let mut output = BufReader::new(child.stdout.take().unwrap());
+
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}"#,
+    );
+    assert!(receive(&mut output).contains(r#""codeActionProvider":true"#));
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "method": "initialized", "params": {}}"#,
+    );
+
+    // Columns are counted in UTF-16 code units, so the emoji counts twice.
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {"textDocument": {"uri": "file:///a.txt", "languageId": "plaintext", "version": 1, "text": "the 😀 wrld"}}}"#,
+    );
+    let diagnostics = receive(&mut output);
+    assert!(diagnostics.contains(r#""method":"textDocument/publishDiagnostics""#));
+    assert!(diagnostics
+        .contains(r#""range":{"end":{"character":11,"line":0},"start":{"character":7,"line":0}}"#));
+    assert!(diagnostics.contains(r#"Unknown word \"wrld\", did you mean world, word?"#));
+
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "id": 2, "method": "textDocument/codeAction", "params": {"textDocument": {"uri": "file:///a.txt"}, "range": {"start": {"line": 0, "character": 8}, "end": {"line": 0, "character": 8}}, "context": {"diagnostics": []}}}"#,
+    );
+    let actions = receive(&mut output);
+    assert!(actions.contains(r#""title":"Replace with \"world\"""#));
+    assert!(actions.contains(r#""file:///a.txt":[{"newText":"world""#));
+    assert!(actions.contains(r#""title":"Add \"wrld\" to dictionary""#));
+
+    // Adding the word clears the diagnostic.
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "id": 3, "method": "workspace/executeCommand", "params": {"command": "didyoumean.addToDictionary", "arguments": ["wrld"]}}"#,
+    );
+    assert!(receive(&mut output).contains(r#""diagnostics":[]"#));
+    assert!(receive(&mut output).contains(r#""id":3"#));
+    assert_eq!(
+        read_to_string(dir.join("personal").join("en")).unwrap(),
+        "wrld\n"
+    );
+
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "id": 4, "method": "shutdown"}"#,
+    );
+    assert!(receive(&mut output).contains(r#""result":null"#));
+    send(&mut input, r#"{"jsonrpc": "2.0", "method": "exit"}"#);
+    assert!(child.wait().unwrap().success());
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn corrupt_word_list_test() {
+    let dir = data_dir("lsp-corrupt");
+    // A word list cut short after it was downloaded, whose sidecar records its full size.
+    write(dir.join("en"), "the\nwor").unwrap();
+    write(dir.join("en.meta"), "size = 15\nsha256 = \"\"\n").unwrap();
+
+    let mut child = command(&dir)
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut input = child.stdin.take().unwrap();
+    let mut output = BufReader::new(child.stdout.take().unwrap());
+
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}"#,
+    );
+    receive(&mut output);
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {"textDocument": {"uri": "file:///a.txt", "languageId": "plaintext", "version": 1, "text": "the wrld"}}}"#,
+    );
+
+    // The word list isn't downloaded again, which would print to standard output, but reported.
+    let message = receive(&mut output);
+    assert!(message.contains(r#""method":"window/showMessage""#));
+    assert!(message.contains("The English word list is corrupt"));
+    assert_eq!(read_to_string(dir.join("en")).unwrap(), "the\nwor");
+
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "id": 2, "method": "shutdown"}"#,
+    );
+    assert!(receive(&mut output).contains(r#""result":null"#));
+    send(&mut input, r#"{"jsonrpc": "2.0", "method": "exit"}"#);
+    assert!(child.wait().unwrap().success());
+    let mut rest = String::new();
+    output.read_to_string(&mut rest).unwrap();
+    assert!(rest.is_empty());
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn invalid_messages_test() {
+    let dir = data_dir("lsp-invalid");
+    write(dir.join("en"), "the\nworld\nword\n").unwrap();
+
+    let mut child = command(&dir)
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut input = child.stdin.take().unwrap();
+    let mut output = BufReader::new(child.stdout.take().unwrap());
+
+    // Asking for too many suggestions gets as many as there are words.
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"initializationOptions": {"number": 1099511627776}}}"#,
+    );
+    receive(&mut output);
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {"textDocument": {"uri": "file:///a.txt", "languageId": "plaintext", "version": 1, "text": "wrld"}}}"#,
+    );
+    assert!(receive(&mut output).contains(r#"did you mean world, word, the?"#));
+
+    // A message that isn't JSON is answered with an error, and the next one is handled.
+    send(&mut input, r#"{"jsonrpc": "2.0", "id": 2,"#);
+    let error = receive(&mut output);
+    assert!(error.contains(r#""code":-32700"#));
+    assert!(error.contains(r#""id":null"#));
+
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "id": 3, "method": "shutdown"}"#,
+    );
+    assert!(receive(&mut output).contains(r#""result":null"#));
+    send(&mut input, r#"{"jsonrpc": "2.0", "method": "exit"}"#);
+    assert!(child.wait().unwrap().success());
+
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn unknown_lang_test() {
+    let dir = data_dir("lsp-lang");
+
+    let mut child = command(&dir)
+        .arg("lsp")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut input = child.stdin.take().unwrap();
+    let mut output = BufReader::new(child.stdout.take().unwrap());
+
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {"initializationOptions": {"lang": "xx"}}}"#,
+    );
+    receive(&mut output);
+
+    // Adding words to the word list of an unknown language says why it can't be done.
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "id": 2, "method": "workspace/executeCommand", "params": {"command": "didyoumean.addToDictionary", "arguments": ["wrld"]}}"#,
+    );
+    let error = receive(&mut output);
+    assert!(error.contains(r#""code":-32602"#));
+    assert!(error.contains("There is no word list for xx"));
+    assert!(!dir.join("personal").join("xx").exists());
+
+    send(
+        &mut input,
+        r#"{"jsonrpc": "2.0", "id": 3, "method": "shutdown"}"#,
+    );
+    assert!(receive(&mut output).contains(r#""result":null"#));
+    send(&mut input, r#"{"jsonrpc": "2.0", "method": "exit"}"#);
+    assert!(child.wait().unwrap().success());
+
+    remove_dir_all(&dir).unwrap();
+}
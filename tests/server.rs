@@ -0,0 +1,196 @@
+mod common;
+
+use common::{command, word_list_dir};
+use std::{
+    fs::{remove_dir_all, write},
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    path::Path,
+    process::{Child, Stdio},
+};
+
+/// Kill the server when the test ends, even if it fails.
+struct Server(Child);
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.0.kill().ok();
+        self.0.wait().ok();
+    }
+}
+
+/// Send a raw HTTP request to `address` and return the head and body of the response.
+fn exchange(address: &str, request: &str) -> (String, String) {
+    let mut stream = TcpStream::connect(address).unwrap();
+    stream.write_all(request.as_bytes()).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    (head.to_owned(), body.to_owned())
+}
+
+/// Send a raw HTTP request to `address` and return the status code and body of the response.
+fn request(address: &str, request: &str) -> (u16, String) {
+    let (head, body) = exchange(address, request);
+    let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+    (status, body)
+}
+
+/// Start a server for the word lists in `dir` and return it with the address it listens on.
+fn start(dir: &Path) -> (Server, String) {
+    let mut server = Server(
+        command(dir)
+            .args(["serve", "--bind", "127.0.0.1:0"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap(),
+    );
+
+    // The server prints the address it listens on once it is ready.
+    let mut line = String::new();
+    BufReader::new(server.0.stdout.take().unwrap())
+        .read_line(&mut line)
+        .unwrap();
+    let address = line.trim().strip_prefix("Listening on http://").unwrap();
+    (server, address.to_owned())
+}
+
+/// The English word list of the tests.
+const WORDS: &str = "hello\nhelp\nworld\n";
+
+#[test]
+fn server_test() {
+    let dir = word_list_dir("routes", WORDS);
+    let (server, address) = start(&dir);
+    let address = address.as_str();
+
+    assert_eq!(
+        request(address, "GET /health HTTP/1.1\r\nHost: dym\r\n\r\n"),
+        (
+            200,
+            format!(
+                "{{\"status\":\"ok\",\"version\":\"{}\"}}",
+                env!("CARGO_PKG_VERSION")
+            )
+        )
+    );
+    assert_eq!(
+        request(address, "GET /suggest?q=helo&n=2 HTTP/1.1\r\n\r\n"),
+        (
+            200,
+            "{\"lang\":\"en\",\"suggestions\":[{\"word\":\"hello\",\"distance\":1},{\"word\":\"help\",\"distance\":1}]}"
+                .to_owned()
+        )
+    );
+
+    let body = r#"{"text": "the wrld", "n": 1}"#;
+    assert_eq!(
+        request(
+            address,
+            &format!(
+                "POST /check HTTP/1.1\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        ),
+        (
+            200,
+            "{\"lang\":\"en\",\"misspellings\":[{\"word\":\"the\",\"line\":1,\"column\":1,\"suggestions\":[\"help\"]},{\"word\":\"wrld\",\"line\":1,\"column\":5,\"suggestions\":[\"world\"]}]}"
+                .to_owned()
+        )
+    );
+
+    assert_eq!(request(address, "GET /suggest HTTP/1.1\r\n\r\n").0, 400);
+    assert_eq!(
+        request(address, "GET /suggest?q=a&lang=fr HTTP/1.1\r\n\r\n"),
+        (
+            404,
+            "{\"error\":\"fr word list is not installed\"}".to_owned()
+        )
+    );
+    assert_eq!(request(address, "DELETE /check HTTP/1.1\r\n\r\n").0, 405);
+
+    drop(server);
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cors_test() {
+    let dir = word_list_dir("cors", WORDS);
+    write(
+        dir.join("config.toml"),
+        "cors_origins = [\"https://example.com\"]\n",
+    )
+    .unwrap();
+    let (server, address) = start(&dir);
+
+    // Preflight requests are answered for allowed origins.
+    let (head, body) = exchange(
+        &address,
+        "OPTIONS /check HTTP/1.1\r\nOrigin: https://example.com\r\nAccess-Control-Request-Method: POST\r\n\r\n",
+    );
+    assert!(head.starts_with("HTTP/1.1 204 No Content\r\n"));
+    assert!(head.contains("\r\nAccess-Control-Allow-Origin: https://example.com\r\n"));
+    assert!(head.contains("\r\nAccess-Control-Allow-Methods: GET, POST, OPTIONS\r\n"));
+    assert!(head.contains("\r\nAccess-Control-Allow-Headers: Content-Type\r\n"));
+    assert!(body.is_empty());
+
+    let (head, _) = exchange(
+        &address,
+        "GET /suggest?q=helo HTTP/1.1\r\nOrigin: https://example.com\r\n\r\n",
+    );
+    assert!(head.contains("\r\nAccess-Control-Allow-Origin: https://example.com\r\n"));
+    assert!(!head.contains("Access-Control-Allow-Methods"));
+
+    // Other origins and requests without an origin get no CORS headers.
+    let (head, _) = exchange(
+        &address,
+        "GET /suggest?q=helo HTTP/1.1\r\nOrigin: https://example.org\r\n\r\n",
+    );
+    assert!(!head.contains("Access-Control"));
+    let (head, _) = exchange(&address, "GET /health HTTP/1.1\r\n\r\n");
+    assert!(!head.contains("Access-Control"));
+
+    drop(server);
+    remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn bounds_test() {
+    let dir = word_list_dir("bounds", WORDS);
+    let (server, address) = start(&dir);
+    let address = address.as_str();
+
+    // Asking for too many suggestions is refused without taking the server down, and asking for
+    // more than there are words returns all of them.
+    assert_eq!(
+        request(
+            address,
+            "GET /suggest?q=helo&n=1099511627776 HTTP/1.1\r\n\r\n"
+        ),
+        (
+            400,
+            "{\"error\":\"The n parameter can't be larger than 1000\"}".to_owned()
+        )
+    );
+    let (status, body) = request(address, "GET /suggest?q=helo&n=1000 HTTP/1.1\r\n\r\n");
+    assert_eq!(status, 200);
+    assert_eq!(body.matches("\"word\"").count(), 3);
+
+    // Escapes are two hexadecimal digits, without a sign.
+    assert_eq!(
+        request(address, "GET /suggest?q=%+1 HTTP/1.1\r\n\r\n").0,
+        400
+    );
+    assert_eq!(
+        request(address, "GET /suggest?q=%68elo&n=1 HTTP/1.1\r\n\r\n"),
+        (
+            200,
+            "{\"lang\":\"en\",\"suggestions\":[{\"word\":\"hello\",\"distance\":1}]}".to_owned()
+        )
+    );
+
+    drop(server);
+    remove_dir_all(&dir).unwrap();
+}